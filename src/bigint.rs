@@ -0,0 +1,222 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+// 1桁あたりの基数（10進で表示しやすいよう10^9）
+const BASE: u64 = 1_000_000_000;
+
+// BigInt: 任意精度の整数（カウンターの値の昇格先）
+// 絶対値を基数BASEの桁で下位から持つ。0は空で、符号は負にならない
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    // i64に収まればその値
+    pub fn to_i64(&self) -> Option<i64> {
+        let mut value: i128 = 0;
+        for &d in self.digits.iter().rev() {
+            value = value * BASE as i128 + d as i128;
+            if value > i64::MAX as i128 + 1 {
+                return None;
+            }
+        }
+        let value = if self.negative { -value } else { value };
+        i64::try_from(value).ok()
+    }
+
+    // 基数が偶数なので最下位の桁だけで決まる
+    pub fn is_even(&self) -> bool {
+        self.digits.first().is_none_or(|d| d % 2 == 0)
+    }
+
+    // 近似値（表示や比率の計算に使う）
+    pub fn to_f64(&self) -> f64 {
+        let value = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0.0, |value, &d| value * BASE as f64 + d as f64);
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return Self::normalized(self.negative, add_magnitude(&self.digits, &other.digits));
+        }
+        // 符号が違えば絶対値の大きい方から小さい方を引く
+        match cmp_magnitude(&self.digits, &other.digits) {
+            Ordering::Less => {
+                Self::normalized(other.negative, sub_magnitude(&other.digits, &self.digits))
+            }
+            _ => Self::normalized(self.negative, sub_magnitude(&self.digits, &other.digits)),
+        }
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        let mut digits = vec![0u64; self.digits.len() + other.digits.len()];
+        for (i, &a) in self.digits.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.digits.iter().enumerate() {
+                let total = digits[i + j] + a as u64 * b as u64 + carry;
+                digits[i + j] = total % BASE;
+                carry = total / BASE;
+            }
+            digits[i + other.digits.len()] += carry;
+        }
+        let digits = digits.into_iter().map(|d| d as u32).collect();
+        Self::normalized(self.negative != other.negative, digits)
+    }
+
+    // 上位の0を除き、0の符号を正にする
+    fn normalized(negative: bool, mut digits: Vec<u32>) -> BigInt {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        BigInt {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut digits = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let total = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        digits.push((total % BASE) as u32);
+        carry = total / BASE;
+    }
+    if carry > 0 {
+        digits.push(carry as u32);
+    }
+    digits
+}
+
+// a >= bであること
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut digits = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, &d) in a.iter().enumerate() {
+        let sub = *b.get(i).unwrap_or(&0) as i64 + borrow;
+        let mut value = d as i64 - sub;
+        borrow = 0;
+        if value < 0 {
+            value += BASE as i64;
+            borrow = 1;
+        }
+        digits.push(value as u32);
+    }
+    digits
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let mut magnitude = value.unsigned_abs();
+        let mut digits = Vec::new();
+        while magnitude > 0 {
+            digits.push((magnitude % BASE) as u32);
+            magnitude /= BASE;
+        }
+        Self::normalized(value < 0, digits)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.digits, &other.digits),
+            (true, true) => cmp_magnitude(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((top, rest)) = self.digits.split_last() else {
+            return f.write_str("0");
+        };
+        if self.negative {
+            f.write_str("-")?;
+        }
+        write!(f, "{top}")?;
+        for d in rest.iter().rev() {
+            write!(f, "{d:09}")?;
+        }
+        Ok(())
+    }
+}
+
+// ParseBigIntError: 整数として読めない文字列
+#[derive(Debug)]
+pub struct ParseBigIntError;
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid integer")
+    }
+}
+
+impl std::error::Error for ParseBigIntError {}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (negative, body) = match text.strip_prefix('-') {
+            Some(body) => (true, body),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        if body.is_empty() || !body.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigIntError);
+        }
+        // 下位から9桁ずつ読む
+        let digits = body
+            .as_bytes()
+            .rchunks(9)
+            .map(|chunk| std::str::from_utf8(chunk).unwrap().parse().unwrap())
+            .collect();
+        Ok(Self::normalized(negative, digits))
+    }
+}
+
+// 保存するときは10進の文字列にする
+#[cfg(feature = "persistence")]
+impl serde::Serialize for BigInt {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl<'de> serde::Deserialize<'de> for BigInt {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
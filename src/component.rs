@@ -0,0 +1,261 @@
+use crate::focus::Focus;
+use crate::mouse::Hits;
+use crate::view::Render;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders},
+    Frame,
+};
+
+// Component: 画面の一部を受け持つ部品
+// 与えられた領域に状態を描画し、フォーカスがあればキー入力をActionにする
+pub trait Component<T, A> {
+    // 描画して、マウスで操作できる領域をHitsに記録する
+    fn render(&mut self, f: &mut Frame, area: Rect, state: &T, focused: bool, hits: &mut Hits<A>);
+
+    // フォーカスを受け取れるか
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    // キーマップで部品ごとの割り当て（"list:up"）に使う名前
+    fn pane(&self) -> Option<&'static str> {
+        None
+    }
+
+    // フォーカスがあるときのキー入力。処理しなければNone（全体のキーマップに回る）
+    // 名前付きのコマンドで済むキーはキーマップの部品ごとの割り当てにする（ヘルプに出て、設定で変えられる）
+    fn handle_key(&mut self, _key: &KeyEvent, _state: &T) -> Option<A> {
+        None
+    }
+}
+
+// 描画関数だけの部品（フォーカスは受け取らない）
+impl<T, A> Component<T, A> for Render<T, A> {
+    fn render(&mut self, f: &mut Frame, area: Rect, state: &T, _: bool, hits: &mut Hits<A>) {
+        self(f, area, state, hits)
+    }
+}
+
+// 部品の枠。フォーカスがあれば太線で強調する
+pub fn pane<'a>(title: impl Into<Line<'a>>, focused: bool) -> Block<'a> {
+    let block = Block::default().title(title).borders(Borders::ALL);
+    if focused {
+        block
+            .border_type(BorderType::Thick)
+            .border_style(Style::default().fg(Color::Cyan))
+    } else {
+        block
+    }
+}
+
+// Node: 部品の木
+pub enum Node<T, A> {
+    Leaf(Leaf<T, A>),
+    Split(Split<T, A>),
+}
+
+// Leaf: 部品と、直前に描画した領域
+pub struct Leaf<T, A> {
+    component: Box<dyn Component<T, A>>,
+    area: Rect,
+    // 最初にフォーカスを置くか
+    autofocus: bool,
+}
+
+// Split: 子を縦か横に並べる入れ物
+pub struct Split<T, A> {
+    direction: Direction,
+    children: Vec<(Constraint, Node<T, A>)>,
+}
+
+impl<T, A> Node<T, A> {
+    pub fn leaf(component: impl Component<T, A> + 'static) -> Self {
+        Node::Leaf(Leaf {
+            component: Box::new(component),
+            area: Rect::default(),
+            autofocus: false,
+        })
+    }
+
+    // 最初にフォーカスを置く部品にする（葉のときだけ）
+    pub fn autofocus(mut self) -> Self {
+        if let Node::Leaf(leaf) = &mut self {
+            leaf.autofocus = true;
+        }
+        self
+    }
+
+    // 葉を左上から順に（木の深さ優先で）集める
+    fn leaves(&self) -> Vec<&Leaf<T, A>> {
+        match self {
+            Node::Leaf(leaf) => vec![leaf],
+            Node::Split(split) => split
+                .children
+                .iter()
+                .flat_map(|(_, c)| c.leaves())
+                .collect(),
+        }
+    }
+
+    fn leaves_mut(&mut self) -> Vec<&mut Leaf<T, A>> {
+        match self {
+            Node::Leaf(leaf) => vec![leaf],
+            Node::Split(split) => split
+                .children
+                .iter_mut()
+                .flat_map(|(_, c)| c.leaves_mut())
+                .collect(),
+        }
+    }
+
+    // 領域を割り当てながら描画する。focusedはフォーカスのある葉の番号
+    fn render(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        state: &T,
+        focused: Option<usize>,
+        next: &mut usize,
+        hits: &mut Hits<A>,
+    ) {
+        match self {
+            Node::Leaf(leaf) => {
+                leaf.area = area;
+                let focus = focused == Some(*next);
+                *next += 1;
+                leaf.component.render(f, area, state, focus, hits);
+            }
+            Node::Split(split) => {
+                let areas = Layout::default()
+                    .direction(split.direction)
+                    .constraints(split.children.iter().map(|(c, _)| *c))
+                    .split(area);
+                for ((_, child), area) in split.children.iter_mut().zip(areas.iter()) {
+                    child.render(f, *area, state, focused, next, hits);
+                }
+            }
+        }
+    }
+}
+
+impl<T, A> Leaf<T, A> {
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    pub fn focusable(&self) -> bool {
+        self.component.focusable()
+    }
+}
+
+impl<T, A> Split<T, A> {
+    pub fn horizontal() -> Self {
+        Self::new(Direction::Horizontal)
+    }
+
+    pub fn vertical() -> Self {
+        Self::new(Direction::Vertical)
+    }
+
+    fn new(direction: Direction) -> Self {
+        Self {
+            direction,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn child(mut self, constraint: Constraint, node: impl Into<Node<T, A>>) -> Self {
+        self.children.push((constraint, node.into()));
+        self
+    }
+}
+
+impl<T, A> From<Split<T, A>> for Node<T, A> {
+    fn from(split: Split<T, A>) -> Self {
+        Node::Split(split)
+    }
+}
+
+impl<T: 'static, A: 'static> From<Render<T, A>> for Node<T, A> {
+    fn from(render: Render<T, A>) -> Self {
+        Node::leaf(render)
+    }
+}
+
+// Tree: 画面全体の部品の木と、フォーカスのある葉
+pub struct Tree<T, A> {
+    root: Node<T, A>,
+    focus: Focus,
+}
+
+impl<T, A> Tree<T, A> {
+    // autofocusの葉、なければ最初にフォーカスを受け取れる葉にフォーカスを置く
+    pub fn new(root: impl Into<Node<T, A>>) -> Self {
+        let root = root.into();
+        let leaves = root.leaves();
+        let initial = leaves.iter().position(|leaf| leaf.autofocus);
+        let focusable: Vec<_> = leaves.iter().map(|leaf| leaf.focusable()).collect();
+        let focus = Focus::new(&focusable, initial);
+        Self { root, focus }
+    }
+
+    pub fn leaves(&self) -> Vec<&Leaf<T, A>> {
+        self.root.leaves()
+    }
+
+    fn focusable(&self) -> Vec<bool> {
+        self.leaves().iter().map(|leaf| leaf.focusable()).collect()
+    }
+
+    // フォーカスのある葉の番号（フォーカスを受け取れる葉がなければNone）
+    pub fn focused(&self) -> Option<usize> {
+        self.focus.current()
+    }
+
+    // 指定した葉にフォーカスを移す。受け取れない葉ならfalse
+    pub fn focus(&mut self, index: usize) -> bool {
+        let focusable = self.focusable();
+        self.focus.set(&focusable, index)
+    }
+
+    pub fn focus_next(&mut self) {
+        let focusable = self.focusable();
+        self.focus.next(&focusable);
+    }
+
+    pub fn focus_prev(&mut self) {
+        let focusable = self.focusable();
+        self.focus.prev(&focusable);
+    }
+
+    // 直前の描画でその位置にあった葉にフォーカスを移す
+    pub fn focus_at(&mut self, position: Position) -> bool {
+        let index = self
+            .leaves()
+            .iter()
+            .position(|leaf| leaf.area.contains(position));
+        index.is_some_and(|i| self.focus(i))
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect, state: &T, hits: &mut Hits<A>) {
+        let focused = self.focus.current();
+        self.root.render(f, area, state, focused, &mut 0, hits);
+    }
+
+    // フォーカスのある部品の名前
+    pub fn pane(&self) -> Option<&'static str> {
+        let index = self.focus.current()?;
+        self.leaves().get(index)?.component.pane()
+    }
+
+    // キー入力をフォーカスのある部品に渡す
+    pub fn handle_key(&mut self, key: &KeyEvent, state: &T) -> Option<A> {
+        let index = self.focus.current()?;
+        let mut leaves = self.root.leaves_mut();
+        leaves.get_mut(index)?.component.handle_key(key, state)
+    }
+}
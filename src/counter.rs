@@ -0,0 +1,1059 @@
+use crate::bigint::BigInt;
+use crate::component::{pane, Component, Node, Split};
+use crate::history::{HistoryOp, Restore, Timeline, Undoable};
+use crate::input::NamedAction;
+use crate::keymap::Preset;
+use crate::mouse::Hits;
+use crate::selector::{Cached, Selector};
+use crate::store::Store;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState, Paragraph, Widget},
+    Frame,
+};
+use std::collections::VecDeque;
+use std::fmt;
+use tokio::time::{Duration, Instant};
+
+// カウンター: Storeの実装例
+// 名前付きのカウンターを並べて持ち、選択中のものを操作する
+#[derive(Clone, PartialEq)]
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "StateFile")
+)]
+pub struct CounterState {
+    // 常に1つ以上
+    pub counters: Vec<Counter>,
+    pub selected: usize,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct Counter {
+    pub name: String,
+    pub count: Value,
+    // 範囲を超える演算の扱い
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub overflow: Overflow,
+    // 値の下限と上限（省略時はi32の範囲）
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub min: Option<i32>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub max: Option<i32>,
+    // Overflow::Errorで拒否した直前の演算（次に値が変わると消える）
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+}
+
+// Value: カウンターの値。Overflow::Promoteでi32を超えたときだけBigになる
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(untagged)
+)]
+pub enum Value {
+    Int(i32),
+    Big(BigInt),
+}
+
+// エラー表示は比べない（拒否した演算は状態を変えていないので履歴に残さない）
+impl PartialEq for Counter {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.count == other.count
+            && self.overflow == other.overflow
+            && self.min == other.min
+            && self.max == other.max
+    }
+}
+
+impl Default for Value {
+    fn default() -> Self {
+        Value::Int(0)
+    }
+}
+
+impl Value {
+    pub fn is_even(&self) -> bool {
+        match self {
+            Value::Int(n) => n % 2 == 0,
+            Value::Big(n) => n.is_even(),
+        }
+    }
+
+    pub fn to_big(&self) -> BigInt {
+        match self {
+            Value::Int(n) => BigInt::from(i64::from(*n)),
+            Value::Big(n) => n.clone(),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Value::Int(n) => f64::from(*n),
+            Value::Big(n) => n.to_f64(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{n}"),
+            Value::Big(n) => write!(f, "{n}"),
+        }
+    }
+}
+
+// Overflow: 演算の結果が範囲（上下限、なければi32）を超えたときの扱い
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Overflow {
+    // 範囲の端で止める
+    #[default]
+    Saturate,
+    // 反対の端に回り込む
+    Wrap,
+    // 演算を行わずにエラーを表示する
+    Error,
+    // i32を超えたら任意精度の整数にする（上下限は守る）
+    Promote,
+}
+
+impl Overflow {
+    pub const ALL: [Overflow; 4] = [
+        Overflow::Saturate,
+        Overflow::Wrap,
+        Overflow::Error,
+        Overflow::Promote,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Overflow::Saturate => "saturate",
+            Overflow::Wrap => "wrap",
+            Overflow::Error => "error",
+            Overflow::Promote => "promote",
+        }
+    }
+}
+
+// 値を変える演算
+#[derive(Clone, Copy)]
+enum Op {
+    Set(i32),
+    Add(i32),
+    Multiply(i32),
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::Set(n) => write!(f, "= {n}"),
+            Op::Add(n) if *n < 0 => write!(f, "- {}", n.unsigned_abs()),
+            Op::Add(n) => write!(f, "+ {n}"),
+            Op::Multiply(n) => write!(f, "* {n}"),
+        }
+    }
+}
+
+impl Default for CounterState {
+    fn default() -> Self {
+        Self {
+            counters: vec![Counter::new(default_name(&[]))],
+            selected: 0,
+        }
+    }
+}
+
+impl Counter {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            count: Value::default(),
+            overflow: Overflow::default(),
+            min: None,
+            max: None,
+            error: None,
+        }
+    }
+
+    // 値が取り得る範囲
+    fn range(&self) -> (i32, i32) {
+        (self.min.unwrap_or(i32::MIN), self.max.unwrap_or(i32::MAX))
+    }
+
+    // 上下限のどちらかに達しているか
+    pub fn at_bound(&self) -> Option<&'static str> {
+        let Value::Int(n) = self.count else {
+            return None;
+        };
+        if Some(n) == self.max {
+            Some("max")
+        } else if Some(n) == self.min {
+            Some("min")
+        } else {
+            None
+        }
+    }
+
+    // 演算の結果を方針と上下限に従って反映する
+    fn apply(&mut self, op: Op) {
+        let (lo, hi) = self.range();
+        let exact = match (&self.count, op) {
+            (_, Op::Set(n)) => BigInt::from(i64::from(n)),
+            (Value::Int(v), Op::Add(n)) => BigInt::from(i64::from(*v) + i64::from(n)),
+            (Value::Int(v), Op::Multiply(n)) => BigInt::from(i64::from(*v) * i64::from(n)),
+            (Value::Big(v), Op::Add(n)) => v.add(&BigInt::from(i64::from(n))),
+            (Value::Big(v), Op::Multiply(n)) => v.mul(&BigInt::from(i64::from(n))),
+        };
+        let (low, high) = (BigInt::from(i64::from(lo)), BigInt::from(i64::from(hi)));
+        if low <= exact && exact <= high {
+            self.count = Value::Int(exact.to_i64().unwrap() as i32);
+            self.error = None;
+            return;
+        }
+
+        self.error = None;
+        match self.overflow {
+            // 上下限がなければi32を超えて続ける
+            Overflow::Promote if self.min.is_none() && self.max.is_none() => {
+                self.count = match exact.to_i64().and_then(|n| i32::try_from(n).ok()) {
+                    Some(n) => Value::Int(n),
+                    None => Value::Big(exact),
+                }
+            }
+            Overflow::Saturate | Overflow::Promote => {
+                self.count = Value::Int(if exact < low { lo } else { hi });
+            }
+            Overflow::Wrap => {
+                // 範囲内の値からの演算ならi64に収まる
+                // 収まらない（範囲外の大きな値が残っていた）ときは端で止める
+                self.count = Value::Int(match exact.to_i64() {
+                    Some(exact) => {
+                        let span = i64::from(hi) - i64::from(lo) + 1;
+                        (i64::from(lo) + (exact - i64::from(lo)).rem_euclid(span)) as i32
+                    }
+                    None if exact < low => lo,
+                    None => hi,
+                });
+            }
+            Overflow::Error => {
+                let limit = match (exact < low, self.min, self.max) {
+                    (true, Some(min), _) => format!("< min {min}"),
+                    (false, _, Some(max)) => format!("> max {max}"),
+                    _ => "overflows".to_owned(),
+                };
+                self.error = Some(format!("{} {op} {limit}", self.count));
+            }
+        }
+    }
+
+    // 読み込んだ値を操作できる形に直す（手で編集したファイルは上下限が逆のこともある）
+    #[cfg(feature = "persistence")]
+    fn repair(&mut self) {
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            if min > max {
+                (self.min, self.max) = (Some(max), Some(min));
+            }
+        }
+        self.clamp();
+    }
+
+    // 方針や上下限を変えたら今の値を範囲に収め直す
+    fn clamp(&mut self) {
+        let (lo, hi) = self.range();
+        let keep_big =
+            self.overflow == Overflow::Promote && self.min.is_none() && self.max.is_none();
+        self.count = match &self.count {
+            Value::Int(n) => Value::Int((*n).clamp(lo, hi)),
+            // 手で編集したファイルでは小さな値も文字列で書かれていることがある
+            Value::Big(n) => match n.to_i64().and_then(|n| i32::try_from(n).ok()) {
+                Some(n) => Value::Int(n.clamp(lo, hi)),
+                None if keep_big => Value::Big(n.clone()),
+                None if *n < BigInt::default() => Value::Int(lo),
+                None => Value::Int(hi),
+            },
+        };
+        self.error = None;
+    }
+}
+
+impl CounterState {
+    // 選択中のカウンター
+    pub fn current(&self) -> &Counter {
+        &self.counters[self.selected]
+    }
+
+    fn current_mut(&mut self) -> &mut Counter {
+        &mut self.counters[self.selected]
+    }
+}
+
+// 保存された状態（カウンターが1つだけだった頃の形式も読む）
+#[cfg(feature = "persistence")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum StateFile {
+    Counters {
+        counters: Vec<Counter>,
+        selected: usize,
+    },
+    Single {
+        count: i32,
+    },
+}
+
+#[cfg(feature = "persistence")]
+impl From<StateFile> for CounterState {
+    fn from(file: StateFile) -> Self {
+        let mut state = Self::default();
+        match file {
+            // 空のリストや範囲外の選択は直して読む
+            StateFile::Counters { counters, selected } if !counters.is_empty() => {
+                state.selected = selected.min(counters.len() - 1);
+                state.counters = counters;
+                state.counters.iter_mut().for_each(Counter::repair);
+            }
+            StateFile::Counters { .. } => {}
+            StateFile::Single { count } => state.counters[0].count = Value::Int(count),
+        }
+        state
+    }
+}
+
+// "counter N"のうち使われていない最小の名前
+fn default_name(counters: &[Counter]) -> String {
+    (1..)
+        .map(|n| format!("counter {n}"))
+        .find(|name| counters.iter().all(|c| c.name != *name))
+        .unwrap()
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum CounterAction {
+    Increment,
+    Decrement,
+    // 指定した値にする
+    Set(i32),
+    // 指定した値を足す
+    Add(i32),
+    // 指定した値を掛ける
+    Multiply(i32),
+    Reset,
+    // 選択中のカウンターの範囲を超えたときの扱いを変える
+    SetOverflow(Overflow),
+    // 選択中のカウンターの下限・上限を設定する（Noneで解除）
+    SetMin(Option<i32>),
+    SetMax(Option<i32>),
+    // カウンターを末尾に追加して選択する（空の名前なら自動で付ける）
+    NewCounter(String),
+    // 選択中のカウンターの名前を変える
+    Rename(String),
+    // 選択中のカウンターを削除する（最後の1つは削除しない）
+    Delete,
+    Select(usize),
+    SelectNext,
+    SelectPrev,
+    // 選択中のカウンターを1つ上・下に移動する
+    MoveUp,
+    MoveDown,
+    Undo,
+    Redo,
+}
+
+impl NamedAction for CounterAction {
+    fn named() -> Vec<(&'static str, Self)> {
+        vec![
+            ("increment", CounterAction::Increment),
+            ("decrement", CounterAction::Decrement),
+            ("reset", CounterAction::Reset),
+            ("double", CounterAction::Multiply(2)),
+            ("negate", CounterAction::Multiply(-1)),
+            (
+                "overflow.saturate",
+                CounterAction::SetOverflow(Overflow::Saturate),
+            ),
+            ("overflow.wrap", CounterAction::SetOverflow(Overflow::Wrap)),
+            (
+                "overflow.error",
+                CounterAction::SetOverflow(Overflow::Error),
+            ),
+            (
+                "overflow.promote",
+                CounterAction::SetOverflow(Overflow::Promote),
+            ),
+            ("bounds.clear_min", CounterAction::SetMin(None)),
+            ("bounds.clear_max", CounterAction::SetMax(None)),
+            ("counter.new", CounterAction::NewCounter(String::new())),
+            ("counter.delete", CounterAction::Delete),
+            ("counter.next", CounterAction::SelectNext),
+            ("counter.prev", CounterAction::SelectPrev),
+            ("counter.move_up", CounterAction::MoveUp),
+            ("counter.move_down", CounterAction::MoveDown),
+            ("undo", CounterAction::Undo),
+            ("redo", CounterAction::Redo),
+        ]
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            CounterAction::Increment => "Increase the count by one",
+            CounterAction::Decrement => "Decrease the count by one",
+            CounterAction::Set(_) => "Set the count to a value",
+            CounterAction::Add(_) => "Add a value to the count",
+            CounterAction::Multiply(2) => "Double the count",
+            CounterAction::Multiply(-1) => "Negate the count",
+            CounterAction::Multiply(_) => "Multiply the count by a value",
+            CounterAction::Reset => "Reset the count to zero",
+            CounterAction::SetOverflow(Overflow::Saturate) => "Stop at the bounds on overflow",
+            CounterAction::SetOverflow(Overflow::Wrap) => "Wrap around the bounds on overflow",
+            CounterAction::SetOverflow(Overflow::Error) => "Reject operations that overflow",
+            CounterAction::SetOverflow(Overflow::Promote) => {
+                "Grow beyond 32 bits on overflow (bounds still apply)"
+            }
+            CounterAction::SetMin(None) => "Remove the minimum",
+            CounterAction::SetMin(Some(_)) => "Set the minimum",
+            CounterAction::SetMax(None) => "Remove the maximum",
+            CounterAction::SetMax(Some(_)) => "Set the maximum",
+            CounterAction::NewCounter(_) => "Add a new counter",
+            CounterAction::Rename(_) => "Rename the selected counter",
+            CounterAction::Delete => "Delete the selected counter",
+            CounterAction::Select(_) => "Select a counter",
+            CounterAction::SelectNext => "Select the next counter",
+            CounterAction::SelectPrev => "Select the previous counter",
+            CounterAction::MoveUp => "Move the selected counter up",
+            CounterAction::MoveDown => "Move the selected counter down",
+            CounterAction::Undo => "Undo the last change",
+            CounterAction::Redo => "Redo the undone change",
+        }
+    }
+
+    // 回数を付けた増減は1回の加算にまとめる（履歴も1件になる）
+    fn with_count(&self, count: u32) -> Vec<Self> {
+        let count = i32::try_from(count).unwrap_or(i32::MAX);
+        match self {
+            CounterAction::Increment => vec![CounterAction::Add(count)],
+            CounterAction::Decrement => vec![CounterAction::Add(-count)],
+            action => vec![action.clone(); count as usize],
+        }
+    }
+}
+
+// 入力欄の文字列をActionにする
+// "+25"や"-3"は加算、"*2"は乗算、"42"や"=-5"はその値にする
+// "new NAME"でカウンターを追加し、"rename NAME"で選択中のものの名前を変える
+// "min N"・"max N"（"none"で解除）で上下限、"overflow wrap"などで範囲を超えたときの扱いを設定する
+pub fn parse(text: &str) -> Result<CounterAction, String> {
+    let text = text.trim();
+    let bound = |s: &str| match s {
+        "none" | "" => Ok(None),
+        s => s
+            .parse::<i32>()
+            .map(Some)
+            .map_err(|_| format!("not a number: {s}")),
+    };
+    if let Some(value) = word(text, "min") {
+        return bound(value).map(CounterAction::SetMin);
+    }
+    if let Some(value) = word(text, "max") {
+        return bound(value).map(CounterAction::SetMax);
+    }
+    if let Some(name) = word(text, "overflow") {
+        return Overflow::ALL
+            .into_iter()
+            .find(|o| o.name() == name)
+            .map(CounterAction::SetOverflow)
+            .ok_or_else(|| "overflow must be one of saturate, wrap, error or promote".to_owned());
+    }
+    if let Some(name) = word(text, "new") {
+        return Ok(CounterAction::NewCounter(name.to_owned()));
+    }
+    if let Some(name) = word(text, "rename") {
+        if name.is_empty() {
+            return Err("rename requires a name".to_owned());
+        }
+        return Ok(CounterAction::Rename(name.to_owned()));
+    }
+    let number = |s: &str| {
+        s.trim()
+            .parse::<i32>()
+            .map_err(|_| format!("not a number: {s}"))
+    };
+    match text.chars().next() {
+        None => Err("enter a value such as 42, +5, -3 or *2".to_owned()),
+        Some('+') => number(&text[1..]).map(CounterAction::Add),
+        // 符号ごと数として読む（i32::MINは符号を外すと収まらない）
+        Some('-') => number(&format!("-{}", text[1..].trim())).map(CounterAction::Add),
+        Some('*' | 'x') => number(&text[1..]).map(CounterAction::Multiply),
+        Some('=') => number(&text[1..]).map(CounterAction::Set),
+        Some(_) => number(text).map(CounterAction::Set),
+    }
+}
+
+// textが単語keywordで始まっていれば、その後ろの文字列
+fn word<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = text.strip_prefix(keyword)?;
+    match rest.chars().next() {
+        None => Some(""),
+        Some(c) if c.is_whitespace() => Some(rest.trim()),
+        Some(_) => None,
+    }
+}
+
+impl Undoable for CounterAction {
+    fn history_op(&self) -> Option<HistoryOp> {
+        match self {
+            CounterAction::Undo => Some(HistoryOp::Undo),
+            CounterAction::Redo => Some(HistoryOp::Redo),
+            _ => None,
+        }
+    }
+}
+
+pub struct CounterStore {
+    state: CounterState,
+}
+
+impl CounterStore {
+    pub fn new() -> Self {
+        Self::with_state(CounterState::default())
+    }
+
+    // 保存しておいた状態から始める
+    pub fn with_state(state: CounterState) -> Self {
+        Self { state }
+    }
+}
+
+impl Default for CounterStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Store for CounterStore {
+    type State = CounterState;
+    type Action = CounterAction;
+
+    fn state(&self) -> &CounterState {
+        &self.state
+    }
+
+    fn reduce(&mut self, action: &CounterAction) {
+        let state = &mut self.state;
+        let last = state.counters.len() - 1;
+        match action {
+            CounterAction::NewCounter(name) => {
+                let name = match name.trim() {
+                    "" => default_name(&state.counters),
+                    name => name.to_owned(),
+                };
+                state.counters.push(Counter::new(name));
+                state.selected = state.counters.len() - 1;
+            }
+            CounterAction::Rename(name) => {
+                if !name.trim().is_empty() {
+                    state.current_mut().name = name.trim().to_owned();
+                }
+            }
+            CounterAction::Delete => {
+                if last > 0 {
+                    state.counters.remove(state.selected);
+                    state.selected = state.selected.min(last - 1);
+                }
+            }
+            CounterAction::Select(i) => state.selected = (*i).min(last),
+            CounterAction::SelectNext => state.selected = (state.selected + 1).min(last),
+            CounterAction::SelectPrev => state.selected = state.selected.saturating_sub(1),
+            CounterAction::MoveUp => {
+                if state.selected > 0 {
+                    state.counters.swap(state.selected, state.selected - 1);
+                    state.selected -= 1;
+                }
+            }
+            CounterAction::MoveDown => {
+                if state.selected < last {
+                    state.counters.swap(state.selected, state.selected + 1);
+                    state.selected += 1;
+                }
+            }
+            // 履歴操作はHistoryが処理する
+            CounterAction::Undo | CounterAction::Redo => {}
+            CounterAction::SetOverflow(overflow) => {
+                let counter = state.current_mut();
+                counter.overflow = *overflow;
+                counter.clamp();
+            }
+            CounterAction::SetMin(min) => {
+                let counter = state.current_mut();
+                match (min, counter.max) {
+                    (Some(min), Some(max)) if *min > max => {
+                        counter.error = Some(format!("minimum {min} exceeds the maximum {max}"));
+                    }
+                    _ => {
+                        counter.min = *min;
+                        counter.clamp();
+                    }
+                }
+            }
+            CounterAction::SetMax(max) => {
+                let counter = state.current_mut();
+                match (counter.min, max) {
+                    (Some(min), Some(max)) if min > *max => {
+                        counter.error = Some(format!("maximum {max} is below the minimum {min}"));
+                    }
+                    _ => {
+                        counter.max = *max;
+                        counter.clamp();
+                    }
+                }
+            }
+            CounterAction::Increment => state.current_mut().apply(Op::Add(1)),
+            CounterAction::Decrement => state.current_mut().apply(Op::Add(-1)),
+            CounterAction::Set(n) => state.current_mut().apply(Op::Set(*n)),
+            CounterAction::Add(n) => state.current_mut().apply(Op::Add(*n)),
+            CounterAction::Multiply(n) => state.current_mut().apply(Op::Multiply(*n)),
+            CounterAction::Reset => state.current_mut().apply(Op::Set(0)),
+        }
+    }
+}
+
+impl Restore for CounterStore {
+    fn restore(&mut self, state: CounterState) {
+        self.state = state;
+    }
+}
+
+// 変化の速さを求める期間
+pub const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+// 値が変わらなくても変化の速さを計算し直す間隔（表示を時間とともに減衰させる）
+// 実行ファイルはこの間隔で再描画する
+pub const RATE_TICK: Duration = Duration::from_millis(500);
+
+// Derived: 画面に出す派生値
+// 状態の一部を入力とするSelectorで持ち、入力が変わったときだけ計算し直す
+pub struct Derived {
+    // 選択中のカウンターの偶奇
+    parity: Selector<CounterState, Value, &'static str>,
+    // 全カウンターの合計
+    total: Selector<CounterState, Vec<Value>, BigInt>,
+    // 選択中のカウンターの1秒あたりの変化（直近RATE_WINDOWの間、測れなければNone）
+    // 時刻も入力に含め、RATE_TICKごとに計算し直す
+    rate: Selector<CounterState, (usize, Value, u128), Option<f64>>,
+    // 派生値の行は、どれかを計算し直したときだけ描き直す
+    stats: Cached<[u64; 3]>,
+}
+
+impl Derived {
+    pub fn new() -> Self {
+        Self {
+            parity: Selector::new(
+                |state: &CounterState| state.current().count.clone(),
+                |count| if count.is_even() { "even" } else { "odd" },
+            ),
+            total: Selector::new(
+                |state: &CounterState| state.counters.iter().map(|c| c.count.clone()).collect(),
+                |counts: &Vec<Value>| {
+                    counts
+                        .iter()
+                        .fold(BigInt::default(), |total, count| total.add(&count.to_big()))
+                },
+            ),
+            rate: {
+                let start = Instant::now();
+                Selector::new(
+                    move |state: &CounterState| {
+                        let tick = start.elapsed().as_millis() / RATE_TICK.as_millis();
+                        (state.selected, state.current().count.clone(), tick)
+                    },
+                    rate(),
+                )
+            },
+            stats: Cached::new(),
+        }
+    }
+
+    pub fn parity(&mut self, state: &CounterState) -> &'static str {
+        self.parity.select(state)
+    }
+
+    pub fn total(&mut self, state: &CounterState) -> &BigInt {
+        self.total.select(state)
+    }
+
+    pub fn rate(&mut self, state: &CounterState) -> Option<f64> {
+        *self.rate.select(state)
+    }
+}
+
+impl Default for Derived {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 値が変わった時刻を覚えておき、直近の期間での変化の速さを求める
+// 最も古い変化から今までの平均なので、変化が止まると減衰し、期間を過ぎるとNoneになる
+// 別のカウンターを選んだら測り直す
+fn rate() -> impl FnMut(&(usize, Value, u128)) -> Option<f64> {
+    let mut samples: VecDeque<(Instant, f64)> = VecDeque::new();
+    let mut selected = None;
+    move |(index, count, _)| {
+        let now = Instant::now();
+        if selected != Some(*index) {
+            samples.clear();
+            selected = Some(*index);
+        }
+        let count = count.to_f64();
+        if samples.back().is_none_or(|&(_, last)| last != count) {
+            samples.push_back((now, count));
+        }
+        while samples.front().is_some_and(|(t, _)| now - *t > RATE_WINDOW) {
+            samples.pop_front();
+        }
+        if samples.len() < 2 {
+            return None;
+        }
+        let (&(first, from), &(_, to)) = (samples.front()?, samples.back()?);
+        let seconds = (now - first).as_secs_f64();
+        (seconds > 0.0).then(|| (to - from) / seconds)
+    }
+}
+
+// 左にカウンターの一覧、右に選択中のカウンターを並べた画面
+pub fn view() -> Split<Timeline<CounterState>, CounterAction> {
+    Split::horizontal()
+        .child(Constraint::Length(20), Node::leaf(CounterList))
+        .child(
+            Constraint::Min(0),
+            Node::leaf(CounterDetail::new()).autofocus(),
+        )
+}
+
+// CounterList: カウンターの一覧。行をクリックするとそのカウンターを選択する
+// フォーカスがあればキーマップの"list:"の割り当てが有効になる（既定では上下キーで選択を移す）
+pub struct CounterList;
+
+impl Component<Timeline<CounterState>, CounterAction> for CounterList {
+    fn render(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        timeline: &Timeline<CounterState>,
+        focused: bool,
+        hits: &mut Hits<CounterAction>,
+    ) {
+        let state = &timeline.current;
+        let block = pane("Counters", focused);
+        let rows = block.inner(area);
+        // 名前は左、値は右に寄せる。収まらなければ名前を切り詰める
+        // 値だけでも収まらなければ、桁を黙って落とさず末尾を…にする
+        let width = usize::from(rows.width);
+        let items: Vec<_> = state
+            .counters
+            .iter()
+            .map(|c| {
+                let mut count = c.count.to_string();
+                if count.len() > width {
+                    count.truncate(width.saturating_sub(1));
+                    count.push('…');
+                }
+                let len = count.chars().count();
+                let room = width.saturating_sub(len + 1);
+                let name: String = c.name.chars().take(room).collect();
+                let pad = width.saturating_sub(name.chars().count() + len);
+                // 名前が残らないときは値を枠いっぱいに使う
+                let pad = if name.is_empty() { pad } else { pad.max(1) };
+                ListItem::new(format!("{name}{}{count}", " ".repeat(pad)))
+            })
+            .collect();
+        let mut list_state = ListState::default().with_selected(Some(state.selected));
+        f.render_stateful_widget(
+            List::new(items)
+                .block(block)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            area,
+            &mut list_state,
+        );
+        // 表示されている行をクリックするとそのカウンターを選択する
+        for (row, i) in (rows.top()..rows.bottom()).zip(list_state.offset()..state.counters.len()) {
+            hits.click(
+                Rect {
+                    y: row,
+                    height: 1,
+                    ..rows
+                },
+                CounterAction::Select(i),
+            );
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn pane(&self) -> Option<&'static str> {
+        Some("list")
+    }
+}
+
+// CounterDetail: 選択中のカウンターと履歴上の位置、派生値、マウスで押せる-/+ボタン
+// フォーカスがあればキーマップの"detail:"の割り当てが有効になる
+pub struct CounterDetail {
+    // 派生値は描画をまたいで持つ
+    derived: Derived,
+}
+
+impl CounterDetail {
+    pub fn new() -> Self {
+        Self {
+            derived: Derived::new(),
+        }
+    }
+}
+
+impl Default for CounterDetail {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component<Timeline<CounterState>, CounterAction> for CounterDetail {
+    fn render(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        timeline: &Timeline<CounterState>,
+        focused: bool,
+        hits: &mut Hits<CounterAction>,
+    ) {
+        let state = &timeline.current;
+        let counter = state.current();
+        let block = pane(counter.name.as_str(), focused);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        // 上から表示、派生値、ボタン、エラー、余白
+        let [text, stats, buttons, error, _] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ])
+            .areas(inner);
+        // 上下限に達していれば値の横に表示する
+        let mut count = vec![Span::raw(format!("Count: {}", counter.count))];
+        if let Some(bound) = counter.at_bound() {
+            count.push(Span::styled(
+                format!(" [{bound}]"),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        let range = match (counter.min, counter.max) {
+            (None, None) => "any".to_owned(),
+            (Some(min), None) => format!("{min}.."),
+            (None, Some(max)) => format!("..={max}"),
+            (Some(min), Some(max)) => format!("{min}..={max}"),
+        };
+        let lines = vec![
+            Line::from(count),
+            Line::from(format!("Range: {range} ({})", counter.overflow.name())),
+            Line::from(format!(
+                "History: {}/{}",
+                timeline.undo,
+                timeline.undo + timeline.redo
+            )),
+        ];
+        f.render_widget(Paragraph::new(lines), text);
+
+        let derived = &mut self.derived;
+        let parity = derived.parity(state);
+        let total = derived.total(state).to_string();
+        let rate = match derived.rate(state) {
+            Some(rate) => format!("{rate:+.1}/s"),
+            None => "-/s".to_owned(),
+        };
+        let key = [
+            derived.parity.version(),
+            derived.total.version(),
+            derived.rate.version(),
+        ];
+        derived
+            .stats
+            .render(f.buffer_mut(), stats, key, |area, buf| {
+                Paragraph::new(format!("Total: {total} · {parity} · {rate}")).render(area, buf)
+            });
+        if let Some(message) = &counter.error {
+            f.render_widget(
+                Paragraph::new(message.as_str()).style(Style::default().fg(Color::Red)),
+                error,
+            );
+        }
+
+        let [minus, _, plus, _] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(5),
+                Constraint::Length(1),
+                Constraint::Length(5),
+                Constraint::Min(0),
+            ])
+            .areas(buttons);
+        f.render_widget(Paragraph::new("[ - ]"), minus);
+        f.render_widget(Paragraph::new("[ + ]"), plus);
+
+        // カウンター全体でホイール、ボタンはクリックで増減する
+        hits.scroll(area, CounterAction::Increment, CounterAction::Decrement);
+        hits.click(minus, CounterAction::Decrement);
+        hits.click(plus, CounterAction::Increment);
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn pane(&self) -> Option<&'static str> {
+        Some("detail")
+    }
+}
+
+// 既定のキーマップ
+const DEFAULT_KEYS: Preset = &[
+    ("up", "increment"),
+    ("down", "decrement"),
+    ("+", "increment"),
+    ("-", "decrement"),
+    ("r", "reset"),
+    ("=", "prompt"),
+    ("shift-down", "counter.next"),
+    ("shift-up", "counter.prev"),
+    ("alt-down", "counter.move_down"),
+    ("alt-up", "counter.move_up"),
+    ("n", "counter.new"),
+    ("delete", "counter.delete"),
+    ("u", "undo"),
+    ("ctrl-r", "redo"),
+    ("[", "debug.back"),
+    ("]", "debug.forward"),
+    ("esc", "debug.resume"),
+    ("m", "macro.record"),
+    ("@", "macro.play"),
+    ("M", "macro.play_named"),
+    ("?", "help"),
+    ("f1", "help"),
+    ("tab", "focus.next"),
+    ("shift-tab", "focus.prev"),
+    (":", "palette"),
+    ("ctrl-p", "palette"),
+    ("ctrl-z", "suspend"),
+    ("q", "quit"),
+    ("ctrl-c", "quit"),
+    // フォーカスのある部品だけの割り当て
+    ("list:up", "counter.prev"),
+    ("list:down", "counter.next"),
+];
+
+// vim風のキーマップ
+const VIM_KEYS: Preset = &[
+    ("k", "increment"),
+    ("up", "increment"),
+    ("j", "decrement"),
+    ("down", "decrement"),
+    ("+", "increment"),
+    ("-", "decrement"),
+    ("r", "reset"),
+    ("=", "prompt"),
+    ("J", "counter.next"),
+    ("K", "counter.prev"),
+    ("alt-j", "counter.move_down"),
+    ("alt-k", "counter.move_up"),
+    ("o", "counter.new"),
+    ("d d", "counter.delete"),
+    ("u", "undo"),
+    ("ctrl-r", "redo"),
+    ("[", "debug.back"),
+    ("]", "debug.forward"),
+    ("esc", "debug.resume"),
+    ("q", "macro.record"),
+    ("@", "macro.play"),
+    ("Q", "macro.play_named"),
+    ("?", "help"),
+    ("f1", "help"),
+    ("tab", "focus.next"),
+    ("shift-tab", "focus.prev"),
+    (":", "palette"),
+    ("ctrl-p", "palette"),
+    ("ctrl-z", "suspend"),
+    ("ctrl-c", "quit"),
+    ("Z Z", "quit"),
+    ("Z Q", "quit"),
+    // フォーカスのある部品だけの割り当て
+    ("list:k", "counter.prev"),
+    ("list:up", "counter.prev"),
+    ("list:j", "counter.next"),
+    ("list:down", "counter.next"),
+];
+
+// emacs風のキーマップ
+const EMACS_KEYS: Preset = &[
+    ("ctrl-p", "increment"),
+    ("up", "increment"),
+    ("ctrl-n", "decrement"),
+    ("down", "decrement"),
+    ("+", "increment"),
+    ("-", "decrement"),
+    ("ctrl-x r", "reset"),
+    ("ctrl-x =", "prompt"),
+    ("ctrl-x o", "counter.next"),
+    ("ctrl-x O", "counter.prev"),
+    ("alt-down", "counter.move_down"),
+    ("alt-up", "counter.move_up"),
+    ("ctrl-x n", "counter.new"),
+    ("ctrl-x k", "counter.delete"),
+    ("ctrl-/", "undo"),
+    ("ctrl-x u", "undo"),
+    ("ctrl-x ctrl-r", "redo"),
+    ("alt-p", "debug.back"),
+    ("alt-n", "debug.forward"),
+    ("ctrl-g", "debug.resume"),
+    ("ctrl-x (", "macro.record"),
+    ("ctrl-x )", "macro.record"),
+    ("ctrl-x e", "macro.play"),
+    ("ctrl-x E", "macro.play_named"),
+    ("?", "help"),
+    ("f1", "help"),
+    ("tab", "focus.next"),
+    ("shift-tab", "focus.prev"),
+    ("alt-x", "palette"),
+    ("ctrl-z", "suspend"),
+    ("ctrl-x ctrl-c", "quit"),
+    // フォーカスのある部品だけの割り当て
+    ("list:ctrl-p", "counter.prev"),
+    ("list:up", "counter.prev"),
+    ("list:ctrl-n", "counter.next"),
+    ("list:down", "counter.next"),
+];
+
+pub const PRESETS: &[(&str, Preset)] = &[
+    ("default", DEFAULT_KEYS),
+    ("vim", VIM_KEYS),
+    ("emacs", EMACS_KEYS),
+];
@@ -0,0 +1,169 @@
+use crate::dispatcher::StoreId;
+use crate::middleware::{Context, Middleware};
+use crate::store::Store;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+// Entry: 記録された1件のActionと適用後の状態
+pub struct Entry<T> {
+    // 記録開始からの経過時間
+    pub at: Duration,
+    pub action: String,
+    pub state: T,
+}
+
+// Log: 記録済みのEntryのうち新しい方から上限件数まで
+pub struct Log<T> {
+    pub entries: VecDeque<Entry<T>>,
+    // 上限を超えて捨てた古いEntryの数
+    pub dropped: usize,
+}
+
+impl<T> Default for Log<T> {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+}
+
+// ActionLog: 記録済みのEntry（Viewはwatchで受け取る）
+pub type ActionLog<T> = watch::Receiver<Log<T>>;
+
+// Recorder: dispatchされたActionと結果の状態を記録するMiddleware
+pub struct Recorder<S: Store> {
+    id: StoreId<S>,
+    log: watch::Sender<Log<S::State>>,
+    // 保持するEntryの上限
+    depth: usize,
+    started: Instant,
+}
+
+impl<S: Store> Recorder<S> {
+    pub fn new(id: StoreId<S>, depth: usize) -> (Self, ActionLog<S::State>) {
+        let (log, rx) = watch::channel(Log::default());
+        let recorder = Self {
+            id,
+            log,
+            depth,
+            started: Instant::now(),
+        };
+        (recorder, rx)
+    }
+}
+
+impl<S: Store> Middleware<S::Action> for Recorder<S>
+where
+    S::Action: Debug,
+{
+    fn after(&mut self, action: &S::Action, ctx: &mut Context<'_, S::Action>) {
+        let entry = Entry {
+            at: self.started.elapsed(),
+            action: format!("{action:?}"),
+            state: ctx.state(self.id).clone(),
+        };
+        // ログ全体を複製せずにその場で追記し、上限を超えた古いEntryから捨てる
+        let depth = self.depth;
+        self.log.send_modify(|log| {
+            log.entries.push_back(entry);
+            while log.entries.len() > depth {
+                log.entries.pop_front();
+                log.dropped += 1;
+            }
+        });
+    }
+}
+
+// Travel: 記録の中を移動する操作
+#[derive(Clone, Copy)]
+pub enum Travel {
+    Back,
+    Forward,
+    Resume,
+}
+
+// DebugPane: Actionログを表示し、選択した時点の状態を返す
+pub struct DebugPane<T> {
+    log: ActionLog<T>,
+    // 選択中のEntryの記録開始からの通し番号（Noneなら最新の状態を表示）
+    selected: Option<usize>,
+}
+
+impl<T: Clone> DebugPane<T> {
+    pub fn new(log: ActionLog<T>) -> Self {
+        Self {
+            log,
+            selected: None,
+        }
+    }
+
+    // 過去の時点を表示しているか
+    pub fn is_paused(&self) -> bool {
+        self.selected.is_some()
+    }
+
+    pub fn travel(&mut self, travel: Travel) {
+        let log = self.log.borrow();
+        let (first, end) = (log.dropped, log.dropped + log.entries.len());
+        // 選択中のEntryが捨てられていたら残っている最も古いEntryにする
+        let selected = self.selected.map(|i| i.max(first));
+        self.selected = match (travel, selected) {
+            (Travel::Resume, _) => None,
+            (_, _) if first == end => None,
+            (Travel::Back, None) => Some(end - 1),
+            (Travel::Back, Some(i)) => Some(i.saturating_sub(1).max(first)),
+            (Travel::Forward, Some(i)) if i + 1 < end => Some(i + 1),
+            (Travel::Forward, other) => other,
+        };
+    }
+
+    // 選択中のEntryのlog.entries内の位置
+    fn position(&self, log: &Log<T>) -> Option<usize> {
+        let i = self.selected?.saturating_sub(log.dropped);
+        Some(i.min(log.entries.len().checked_sub(1)?))
+    }
+
+    // 選択中のEntryの状態（最新を表示中ならNone）
+    pub fn selected_state(&self) -> Option<T> {
+        let log = self.log.borrow();
+        self.position(&log)
+            .and_then(|i| log.entries.get(i))
+            .map(|e| e.state.clone())
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect) {
+        let log = self.log.borrow();
+        let items: Vec<_> = log
+            .entries
+            .iter()
+            .map(|e| ListItem::new(format!("{:>8.3}s {}", e.at.as_secs_f64(), e.action)))
+            .collect();
+
+        let title = if self.is_paused() {
+            "Actions (paused)"
+        } else {
+            "Actions"
+        };
+        let mut list = List::new(items).block(Block::default().title(title).borders(Borders::ALL));
+        // 最新を表示中は末尾に追従し、強調表示はしない
+        let mut state = ListState::default();
+        match self.position(&log) {
+            Some(i) => {
+                state.select(Some(i));
+                list = list.highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            }
+            None => state.select(log.entries.len().checked_sub(1)),
+        }
+        f.render_stateful_widget(list, area, &mut state);
+    }
+}
@@ -0,0 +1,306 @@
+use crate::middleware::{Context, Middleware};
+use crate::store::Store;
+use std::any::{type_name, Any};
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use tokio::sync::{mpsc, oneshot, watch};
+
+// StoreId: 登録したStoreを指すハンドル
+pub struct StoreId<S> {
+    pub(crate) index: usize,
+    _store: PhantomData<fn() -> S>,
+}
+
+impl<S> Clone for StoreId<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for StoreId<S> {}
+
+// DispatchError: Dispatcherの構成時に検出されたエラー
+#[derive(Debug)]
+pub enum DispatchError {
+    // Store間の依存関係が循環している（依存の連鎖を順に保持）
+    Cycle(Vec<&'static str>),
+}
+
+impl fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DispatchError::Cycle(chain) => {
+                write!(f, "store dependency cycle: {}", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for DispatchError {}
+
+// 型を消去したStore（Actionの型だけを共有する）
+pub(crate) trait AnyStore<A>: Send {
+    fn reduce(&mut self, action: &A);
+    fn publish(&self);
+    fn state(&self) -> &dyn Any;
+    // 最後に通知した状態（処理中のActionが適用される前の状態）
+    fn previous(&self) -> Box<dyn Any>;
+}
+
+struct Registered<S: Store> {
+    store: S,
+    tx: watch::Sender<S::State>,
+}
+
+impl<S: Store> AnyStore<S::Action> for Registered<S> {
+    fn reduce(&mut self, action: &S::Action) {
+        self.store.reduce(action);
+    }
+
+    fn publish(&self) {
+        self.tx.send_replace(self.store.state().clone());
+    }
+
+    fn state(&self) -> &dyn Any {
+        self.store.state()
+    }
+
+    fn previous(&self) -> Box<dyn Any> {
+        Box::new(self.tx.borrow().clone())
+    }
+}
+
+// storeはdependencyの更新後に更新される（whenに一致するActionのみ）
+struct Dependency<A> {
+    store: usize,
+    dependency: usize,
+    when: Box<dyn Fn(&A) -> bool + Send>,
+}
+
+// DispatcherBuilder: Storeと依存関係を登録してからDispatcherを起動する
+pub struct DispatcherBuilder<A> {
+    stores: Vec<Box<dyn AnyStore<A>>>,
+    names: Vec<&'static str>,
+    dependencies: Vec<Dependency<A>>,
+    middlewares: Vec<Box<dyn Middleware<A>>>,
+}
+
+impl<A: Send + 'static> Default for DispatcherBuilder<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Send + 'static> DispatcherBuilder<A> {
+    pub fn new() -> Self {
+        Self {
+            stores: Vec::new(),
+            names: Vec::new(),
+            dependencies: Vec::new(),
+            middlewares: Vec::new(),
+        }
+    }
+
+    // Storeを登録し、状態変更の通知を受け取るReceiverを返す
+    pub fn register<S: Store<Action = A>>(
+        &mut self,
+        store: S,
+    ) -> (StoreId<S>, watch::Receiver<S::State>) {
+        let (tx, rx) = watch::channel(store.state().clone());
+        let index = self.stores.len();
+        self.stores.push(Box::new(Registered { store, tx }));
+        self.names.push(type_name::<S>());
+        let id = StoreId {
+            index,
+            _store: PhantomData,
+        };
+        (id, rx)
+    }
+
+    // すべてのActionについて、storeをdependencyの後に更新する
+    pub fn wait_for<S, T>(
+        &mut self,
+        store: StoreId<S>,
+        dependency: StoreId<T>,
+    ) -> Result<(), DispatchError> {
+        self.wait_for_if(store, dependency, |_| true)
+    }
+
+    // whenに一致するActionについて、storeをdependencyの後に更新する
+    // 循環する依存関係は実行時のデッドロックではなくここでエラーになる
+    pub fn wait_for_if<S, T>(
+        &mut self,
+        store: StoreId<S>,
+        dependency: StoreId<T>,
+        when: impl Fn(&A) -> bool + Send + 'static,
+    ) -> Result<(), DispatchError> {
+        // 条件は実行時まで分からないため、どのActionでも循環し得ないことを保証する
+        if let Some(path) = self.path(store.index, dependency.index) {
+            let mut chain: Vec<_> = path.iter().map(|&i| self.names[i]).collect();
+            chain.push(self.names[store.index]);
+            return Err(DispatchError::Cycle(chain));
+        }
+        self.dependencies.push(Dependency {
+            store: store.index,
+            dependency: dependency.index,
+            when: Box::new(when),
+        });
+        Ok(())
+    }
+
+    // Middlewareを末尾に追加する
+    pub fn middleware(&mut self, middleware: impl Middleware<A> + 'static) {
+        self.middlewares.push(Box::new(middleware));
+    }
+
+    // fromの更新後に更新されるStoreをたどってtoに至る経路を探す
+    fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut stack = vec![vec![from]];
+        let mut visited = vec![false; self.stores.len()];
+        while let Some(path) = stack.pop() {
+            let last = *path.last().unwrap();
+            if last == to {
+                return Some(path);
+            }
+            if std::mem::replace(&mut visited[last], true) {
+                continue;
+            }
+            for dep in self.dependencies.iter().filter(|d| d.dependency == last) {
+                let mut next = path.clone();
+                next.push(dep.store);
+                stack.push(next);
+            }
+        }
+        None
+    }
+
+    // Actionに対するStoreの更新順序（依存先が先、それ以外は登録順）
+    fn order(&self, action: &A) -> Vec<usize> {
+        let mut waiting = vec![0; self.stores.len()];
+        let active: Vec<_> = self
+            .dependencies
+            .iter()
+            .filter(|d| (d.when)(action))
+            .collect();
+        for dep in &active {
+            waiting[dep.store] += 1;
+        }
+
+        let mut order = Vec::with_capacity(self.stores.len());
+        let mut done = vec![false; self.stores.len()];
+        while order.len() < self.stores.len() {
+            // 登録時に循環を排除しているので必ず次のStoreが見つかる
+            let next = (0..self.stores.len())
+                .find(|&i| !done[i] && waiting[i] == 0)
+                .unwrap();
+            done[next] = true;
+            order.push(next);
+            for dep in active.iter().filter(|d| d.dependency == next) {
+                waiting[dep.store] -= 1;
+            }
+        }
+        order
+    }
+
+    // Dispatcherの専用タスクを起動する
+    pub fn spawn(mut self) -> Dispatcher<A> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            // 単一のキューから取り出すので適用順序は常に一意
+            while let Some(message) = rx.recv().await {
+                let action = match message {
+                    Message::Action(action) => action,
+                    // ここまでに積まれたActionはすべて適用済み
+                    Message::Flush(done) => {
+                        let _ = done.send(());
+                        continue;
+                    }
+                };
+                // Middlewareが発行した後続Actionは次のActionより先に処理する
+                let mut queue = VecDeque::from([action]);
+                while let Some(action) = queue.pop_front() {
+                    let mut emitted = Vec::new();
+                    self.process(action, &mut emitted);
+                    queue.extend(emitted);
+                }
+            }
+        });
+
+        Dispatcher { tx }
+    }
+
+    // 1つのActionをMiddlewareとStoreに通す
+    fn process(&mut self, mut action: A, emitted: &mut Vec<A>) {
+        for middleware in self.middlewares.iter_mut() {
+            let mut ctx = Context::new(&self.stores, emitted);
+            match middleware.before(action, &mut ctx) {
+                Some(next) => action = next,
+                // 中断されたActionはStoreに届かない
+                None => return,
+            }
+        }
+
+        for index in self.order(&action) {
+            self.stores[index].reduce(&action);
+        }
+
+        for middleware in self.middlewares.iter_mut().rev() {
+            let mut ctx = Context::new(&self.stores, emitted);
+            middleware.after(&action, &mut ctx);
+        }
+
+        // すべてのStoreが更新されてから通知する
+        for store in &self.stores {
+            store.publish();
+        }
+    }
+}
+
+// Dispatcherのキューに積まれるもの
+enum Message<A> {
+    Action(A),
+    Flush(oneshot::Sender<()>),
+}
+
+// Dispatcher: Actionをキューに積み、専用タスクが順番に全Storeへ配信する
+// クローンしてバックグラウンドタスクからも並行してdispatchできる
+pub struct Dispatcher<A> {
+    tx: mpsc::UnboundedSender<Message<A>>,
+}
+
+impl<A> Clone for Dispatcher<A> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<A: Send + 'static> Dispatcher<A> {
+    pub fn builder() -> DispatcherBuilder<A> {
+        DispatcherBuilder::new()
+    }
+
+    // Storeが1つだけの場合の簡易版
+    pub fn spawn<S: Store<Action = A>>(store: S) -> (Self, watch::Receiver<S::State>) {
+        let mut builder = Self::builder();
+        let (_, state) = builder.register(store);
+        (builder.spawn(), state)
+    }
+
+    // ActionをDispatcher経由でStoreに送信
+    pub fn dispatch(&self, action: A) {
+        // 受信側タスクが終了している場合は何もしない
+        let _ = self.tx.send(Message::Action(action));
+    }
+
+    // それまでにdispatchしたActionがすべて適用され、通知されるまで待つ
+    pub async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.tx.send(Message::Flush(done)).is_ok() {
+            let _ = wait.await;
+        }
+    }
+}
@@ -0,0 +1,52 @@
+// Focus: フォーカスのある部品（葉の番号）を覚え、受け取れる部品の間で順に移す
+// focusableは葉の番号ごとにフォーカスを受け取れるか
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Focus {
+    current: Option<usize>,
+}
+
+impl Focus {
+    // initialが受け取れなければ最初に受け取れる部品にする
+    pub fn new(focusable: &[bool], initial: Option<usize>) -> Self {
+        let mut focus = Self::default();
+        if !initial.is_some_and(|i| focus.set(focusable, i)) {
+            focus.current = focusable.iter().position(|&f| f);
+        }
+        focus
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    // 受け取れない部品ならfalseで、フォーカスは動かない
+    pub fn set(&mut self, focusable: &[bool], index: usize) -> bool {
+        let ok = focusable.get(index).copied().unwrap_or(false);
+        if ok {
+            self.current = Some(index);
+        }
+        ok
+    }
+
+    // 次に受け取れる部品へ（最後からは最初に戻る）
+    pub fn next(&mut self, focusable: &[bool]) {
+        self.step(focusable, 1);
+    }
+
+    // 前に受け取れる部品へ（最初からは最後に戻る）
+    pub fn prev(&mut self, focusable: &[bool]) {
+        self.step(focusable, focusable.len().saturating_sub(1));
+    }
+
+    fn step(&mut self, focusable: &[bool], by: usize) {
+        let len = focusable.len();
+        let Some(start) = self.current else {
+            self.current = focusable.iter().position(|&f| f);
+            return;
+        };
+        self.current = (1..=len)
+            .map(|i| (start + by * i) % len)
+            .find(|&i| focusable[i])
+            .or(self.current);
+    }
+}
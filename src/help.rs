@@ -0,0 +1,204 @@
+use crate::keymap::Keymap;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+// Command: 1つのコマンドと割り当てられたすべてのキー列
+struct Command<'a> {
+    name: &'a str,
+    description: &'static str,
+    keys: Vec<String>,
+    // フォーカスのある部品だけの割り当ての位置（ステータスバーにはこのキーを出す）
+    pane: Option<usize>,
+}
+
+// コマンドごとにキー列をまとめる（キーマップで最初に現れた順）
+// フォーカスのある部品で有効な割り当てだけを、部品の割り当てがあるコマンドを先にして並べる
+fn commands<A: Clone>(keymap: &Keymap<A>) -> Vec<Command<'_>> {
+    let mut commands: Vec<Command> = Vec::new();
+    for binding in keymap.active() {
+        let keys = binding.keys.to_string();
+        let pane = binding.pane.is_some();
+        match commands.iter_mut().find(|c| c.name == binding.name) {
+            Some(command) => {
+                if pane && command.pane.is_none() {
+                    command.pane = Some(command.keys.len());
+                }
+                command.keys.push(keys);
+            }
+            None => commands.push(Command {
+                name: &binding.name,
+                description: binding.description,
+                keys: vec![keys],
+                pane: pane.then_some(0),
+            }),
+        }
+    }
+    commands.sort_by_key(|c| c.pane.is_none());
+    commands
+}
+
+// 画面下の1行に主なキー割り当てを表示する
+// ヘルプを先頭に、終了を末尾に必ず置き、その間にキーマップの順に収まるだけ並べる
+// 右端には入力途中の回数とキー列を表示する
+pub fn status_bar<A: Clone>(f: &mut Frame, area: Rect, keymap: &Keymap<A>) {
+    let mut pending: Vec<_> = keymap.count().map(|n| n.to_string()).into_iter().collect();
+    pending.extend(keymap.pending().iter().map(|chord| chord.to_string()));
+    let pending = pending.join(" ");
+    let [area, right] = Layout::horizontal([
+        Constraint::Min(0),
+        Constraint::Length(clamp(pending.chars().count())),
+    ])
+    .areas(area);
+    f.render_widget(Paragraph::new(pending), right);
+
+    let mut commands = commands(keymap);
+    let mut take = |name: &str| {
+        let i = commands.iter().position(|c| c.name == name)?;
+        Some(commands.remove(i))
+    };
+    let help = take("help");
+    let quit = take("quit");
+
+    let key_style = Style::default().add_modifier(Modifier::REVERSED);
+    let entry = |command: &Command| {
+        let key = format!(" {} ", command.keys[command.pane.unwrap_or(0)]);
+        let name = format!(" {}  ", command.name);
+        let width = key.chars().count() + name.chars().count();
+        (width, [Span::styled(key, key_style), Span::raw(name)])
+    };
+    let (quit_width, quit) = match &quit {
+        Some(quit) => {
+            let (width, spans) = entry(quit);
+            (width, spans.to_vec())
+        }
+        None => (0, Vec::new()),
+    };
+    let available = usize::from(area.width).saturating_sub(quit_width);
+    let mut spans = Vec::new();
+    let mut width = 0;
+    for command in help.iter().chain(&commands) {
+        let (w, entry) = entry(command);
+        width += w;
+        if width > available {
+            break;
+        }
+        spans.extend(entry);
+    }
+    spans.extend(quit);
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+// 一覧のスクロールでページ送りに使う行数の既定値（描画前に使われたとき）
+const PAGE: usize = 10;
+
+// Overlay: すべてのキー割り当てと説明の一覧
+// 画面に収まらなければ上下キーなどでスクロールする
+pub struct Overlay {
+    scroll: usize,
+    // 直前の描画で表示できた行数
+    page: usize,
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Self {
+            scroll: 0,
+            page: PAGE,
+        }
+    }
+}
+
+impl Overlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // スクロールのキーならtrue（それ以外はキーマップに回す）
+    // 開いている間は下の画面を操作しないよう、収まっていてもスクロールのキーは受け取る
+    pub fn handle(&mut self, key: &KeyEvent) -> bool {
+        if key.kind != KeyEventKind::Press || !key.modifiers.is_empty() {
+            return false;
+        }
+        self.scroll = match key.code {
+            KeyCode::Up => self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll.saturating_add(1),
+            KeyCode::PageUp => self.scroll.saturating_sub(self.page),
+            KeyCode::PageDown => self.scroll.saturating_add(self.page),
+            KeyCode::Home => 0,
+            // 描画のときに最後のページに収める
+            KeyCode::End => usize::MAX,
+            _ => return false,
+        };
+        true
+    }
+
+    // 画面中央に重ねて表示する
+    pub fn render<A: Clone>(&mut self, f: &mut Frame, area: Rect, keymap: &Keymap<A>) {
+        let commands = commands(keymap);
+        let keys: Vec<_> = commands.iter().map(|c| c.keys.join(", ")).collect();
+        let key_width = keys.iter().map(|k| k.chars().count()).max().unwrap_or(0);
+        let name_width = commands
+            .iter()
+            .map(|c| c.name.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let lines: Vec<_> = commands
+            .iter()
+            .zip(&keys)
+            .map(|(command, keys)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{keys:<key_width$}  "),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format!("{:<name_width$}  ", command.name)),
+                    Span::raw(command.description),
+                ])
+            })
+            .collect();
+
+        // 枠の分を足した大きさで中央に置く（画面より大きければ画面に合わせる）
+        let width = lines.iter().map(Line::width).max().unwrap_or(0) + 2;
+        let height = lines.len() + 2;
+        let [popup] = Layout::horizontal([Constraint::Length(clamp(width))])
+            .flex(Flex::Center)
+            .areas(area);
+        let [popup] = Layout::vertical([Constraint::Length(clamp(height))])
+            .flex(Flex::Center)
+            .areas(popup);
+
+        // 収まらない分はスクロールし、タイトルに表示中の範囲を出す
+        let rows = usize::from(popup.height.saturating_sub(2)).max(1);
+        self.page = rows;
+        self.scroll = self.scroll.min(lines.len().saturating_sub(rows));
+        let title = if rows < lines.len() {
+            let last = (self.scroll + rows).min(lines.len());
+            format!(
+                "Help {}-{last} of {} (↑/↓ to scroll)",
+                self.scroll + 1,
+                lines.len()
+            )
+        } else {
+            "Help".to_owned()
+        };
+
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(lines)
+                .scroll((clamp(self.scroll), 0))
+                .block(Block::default().title(title).borders(Borders::ALL)),
+            popup,
+        );
+    }
+}
+
+fn clamp(len: usize) -> u16 {
+    u16::try_from(len).unwrap_or(u16::MAX)
+}
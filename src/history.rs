@@ -0,0 +1,107 @@
+use crate::store::Store;
+use std::collections::VecDeque;
+
+// HistoryOp: 履歴を操作するAction
+pub enum HistoryOp {
+    Undo,
+    Redo,
+}
+
+// Undoable: Actionのうち履歴操作にあたるものを識別する
+pub trait Undoable {
+    fn history_op(&self) -> Option<HistoryOp>;
+}
+
+// Restore: 保存しておいた状態にStoreを戻す
+pub trait Restore: Store {
+    fn restore(&mut self, state: Self::State);
+}
+
+// Timeline: 現在の状態と履歴上の位置
+#[derive(Clone)]
+pub struct Timeline<T> {
+    pub current: T,
+    // Undoできる回数
+    pub undo: usize,
+    // Redoできる回数
+    pub redo: usize,
+}
+
+// History: Storeを包み、状態の履歴を上限件数まで保持する
+pub struct History<S: Store> {
+    store: S,
+    past: VecDeque<S::State>,
+    future: Vec<S::State>,
+    depth: usize,
+    timeline: Timeline<S::State>,
+}
+
+impl<S: Restore> History<S> {
+    pub fn new(store: S, depth: usize) -> Self {
+        let timeline = Timeline {
+            current: store.state().clone(),
+            undo: 0,
+            redo: 0,
+        };
+        Self {
+            store,
+            past: VecDeque::new(),
+            future: Vec::new(),
+            depth,
+            timeline,
+        }
+    }
+
+    fn sync(&mut self) {
+        self.timeline = Timeline {
+            current: self.store.state().clone(),
+            undo: self.past.len(),
+            redo: self.future.len(),
+        };
+    }
+}
+
+impl<S> Store for History<S>
+where
+    S: Restore,
+    S::State: PartialEq,
+    S::Action: Undoable,
+{
+    type State = Timeline<S::State>;
+    type Action = S::Action;
+
+    fn state(&self) -> &Timeline<S::State> {
+        &self.timeline
+    }
+
+    fn reduce(&mut self, action: &S::Action) {
+        match action.history_op() {
+            Some(HistoryOp::Undo) => {
+                if let Some(state) = self.past.pop_back() {
+                    self.future.push(self.store.state().clone());
+                    self.store.restore(state);
+                }
+            }
+            Some(HistoryOp::Redo) => {
+                if let Some(state) = self.future.pop() {
+                    self.past.push_back(self.store.state().clone());
+                    self.store.restore(state);
+                }
+            }
+            None => {
+                let before = self.store.state().clone();
+                self.store.reduce(action);
+                // 状態が変わらなかったActionは履歴に残さない（Undoしても何も起きないため）
+                if *self.store.state() != before {
+                    self.past.push_back(before);
+                    // 上限を超えた古い履歴から捨てる
+                    while self.past.len() > self.depth {
+                        self.past.pop_front();
+                    }
+                    self.future.clear();
+                }
+            }
+        }
+        self.sync();
+    }
+}
@@ -0,0 +1,89 @@
+use crate::debug::Travel;
+
+// Input: ユーザー入力の解釈結果
+#[derive(Clone)]
+pub enum Input<A> {
+    Action(A),
+    // デバッグパネルのActionログを移動する
+    TimeTravel(Travel),
+    // キー入力のマクロ記録を開始・終了する
+    MacroRecord,
+    // 最後に記録したマクロを再生する
+    MacroPlay,
+    // 保存したマクロを名前で選んで再生する
+    MacroPlayNamed,
+    // キー割り当ての一覧を表示・非表示にする
+    Help,
+    // フォーカスを次・前の部品に移す
+    FocusNext,
+    FocusPrev,
+    // コマンドを名前で検索して実行する
+    Palette,
+    // 入力欄に値や式を入力する
+    Prompt,
+    // 端末を戻してプロセスを一時停止する
+    Suspend,
+    Quit,
+}
+
+// NamedAction: キーマップの設定から名前で参照できるAction
+pub trait NamedAction: Clone {
+    // 名前とActionの一覧
+    fn named() -> Vec<(&'static str, Self)>;
+    // ヘルプに表示する説明
+    fn description(&self) -> &'static str;
+    // "10k"のように回数を付けて入力されたときに実行するAction
+    fn with_count(&self, count: u32) -> Vec<Self> {
+        vec![self.clone(); count as usize]
+    }
+}
+
+impl<A: NamedAction> Input<A> {
+    // 名前で参照できるすべてのコマンド（Actionの後にView自身のコマンド）
+    pub fn named() -> Vec<(&'static str, Self)> {
+        let mut named: Vec<_> = A::named()
+            .into_iter()
+            .map(|(name, action)| (name, Input::Action(action)))
+            .collect();
+        named.extend([
+            ("debug.back", Input::TimeTravel(Travel::Back)),
+            ("debug.forward", Input::TimeTravel(Travel::Forward)),
+            ("debug.resume", Input::TimeTravel(Travel::Resume)),
+            ("macro.record", Input::MacroRecord),
+            ("macro.play", Input::MacroPlay),
+            ("macro.play_named", Input::MacroPlayNamed),
+            ("help", Input::Help),
+            ("focus.next", Input::FocusNext),
+            ("focus.prev", Input::FocusPrev),
+            ("palette", Input::Palette),
+            ("prompt", Input::Prompt),
+            ("suspend", Input::Suspend),
+            ("quit", Input::Quit),
+        ]);
+        named
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (_, input) = Self::named().into_iter().find(|(n, _)| *n == name)?;
+        Some(input)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Input::Action(action) => action.description(),
+            Input::TimeTravel(Travel::Back) => "Select the previous action in the debug log",
+            Input::TimeTravel(Travel::Forward) => "Select the next action in the debug log",
+            Input::TimeTravel(Travel::Resume) => "Return to the live state",
+            Input::MacroRecord => "Start or stop recording a macro",
+            Input::MacroPlay => "Play the last recorded macro",
+            Input::MacroPlayNamed => "Play a saved macro by name",
+            Input::Help => "Show or hide this help",
+            Input::FocusNext => "Focus the next pane",
+            Input::FocusPrev => "Focus the previous pane",
+            Input::Palette => "Search and run a command",
+            Input::Prompt => "Enter a value or an expression",
+            Input::Suspend => "Suspend to the shell",
+            Input::Quit => "Quit",
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::dispatcher::StoreId;
+use crate::history::{Restore, Undoable};
+use crate::middleware::{Context, Middleware};
+use crate::persist;
+use crate::store::Store;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// Record: ジャーナルの1行
+// 先頭は必ずStateで、以降のActionはその状態から順に適用する
+// Undo/Redoは履歴に依存するので、結果の状態をStateとして記録する
+#[derive(Serialize, Deserialize)]
+enum Record<A, T> {
+    Action(A),
+    State(T),
+}
+
+// JournalError: ジャーナルを再生できなかった理由
+#[derive(Debug)]
+pub enum JournalError {
+    Io(PathBuf, io::Error),
+    // 最終行以外の記録が壊れている
+    Corrupt {
+        path: PathBuf,
+        line: usize,
+        error: serde_json::Error,
+    },
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            JournalError::Corrupt { path, line, error } => {
+                write!(f, "{}:{}: corrupt record: {}", path.display(), line, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JournalError {}
+
+// Replay: 再生の結果
+pub struct Replay {
+    // 適用した記録の数
+    pub records: usize,
+    // 最終行が書き込み途中で途切れていた（クラッシュの痕跡）
+    pub torn: bool,
+}
+
+// ジャーナルを読み込み、記録を順にstoreへ適用する（ファイルがなければNone）
+pub fn replay<S>(path: &Path, store: &mut S) -> Result<Option<Replay>, JournalError>
+where
+    S: Restore,
+    S::Action: DeserializeOwned,
+    S::State: DeserializeOwned,
+{
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(JournalError::Io(path.to_owned(), e)),
+    };
+
+    let mut replay = Replay {
+        records: 0,
+        torn: false,
+    };
+    let lines: Vec<_> = text.split_inclusive('\n').collect();
+    for (i, line) in lines.iter().enumerate() {
+        let last = i + 1 == lines.len();
+        match serde_json::from_str::<Record<S::Action, S::State>>(line) {
+            // 改行で終わっていない最終行は書き込み途中の可能性がある
+            Ok(_) if last && !line.ends_with('\n') => replay.torn = true,
+            Ok(Record::Action(action)) => store.reduce(&action),
+            Ok(Record::State(state)) => store.restore(state),
+            Err(_) if last => replay.torn = true,
+            Err(error) => {
+                return Err(JournalError::Corrupt {
+                    path: path.to_owned(),
+                    line: i + 1,
+                    error,
+                })
+            }
+        }
+        if !replay.torn {
+            replay.records += 1;
+        }
+    }
+    Ok(Some(replay))
+}
+
+// Storeの状態からジャーナルに記録する状態を取り出す関数
+type Select<T, U> = Box<dyn Fn(&T) -> U + Send>;
+
+// Journal: dispatchされたActionをファイルに追記するMiddleware
+pub struct Journal<S: Store, T> {
+    id: StoreId<S>,
+    path: PathBuf,
+    file: File,
+    select: Select<S::State, T>,
+    // この件数を書いたらcompactする
+    compact_every: usize,
+    written: usize,
+}
+
+impl<S: Store, T: Serialize> Journal<S, T> {
+    // initialの状態だけを持つジャーナルを作り直して開く
+    pub fn open(
+        path: PathBuf,
+        id: StoreId<S>,
+        initial: &T,
+        select: impl Fn(&S::State) -> T + Send + 'static,
+        compact_every: usize,
+    ) -> io::Result<Self> {
+        let file = rewrite(&path, initial)?;
+        Ok(Self {
+            id,
+            path,
+            file,
+            select: Box::new(select),
+            compact_every,
+            written: 0,
+        })
+    }
+
+    fn append<A: Serialize>(&mut self, record: &Record<&A, &T>) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        // 1回の書き込みで1行を書くので、途切れるのは最終行だけ
+        self.file.write_all(&line)?;
+        self.file.flush()
+    }
+
+    // 現在の状態だけを持つジャーナルに置き換える
+    fn compact(&mut self, state: &T) -> io::Result<()> {
+        self.file = rewrite(&self.path, state)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+// 状態1件だけのジャーナルを一時ファイルに書き、置き換えてから追記用に開く
+fn rewrite<T: Serialize>(path: &Path, state: &T) -> io::Result<File> {
+    let mut line = serde_json::to_vec(&Record::<(), &T>::State(state))?;
+    line.push(b'\n');
+    persist::replace(path, |file| file.write_all(&line))?;
+    OpenOptions::new().append(true).open(path)
+}
+
+// 壊れたジャーナルを作り直す前に、残っている記録を失わないよう脇に移す
+// 移した先のパス（元の名前に.corruptを付けたもの）を返す
+pub fn set_aside(path: &Path) -> io::Result<PathBuf> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".corrupt");
+    let aside = PathBuf::from(name);
+    fs::rename(path, &aside)?;
+    Ok(aside)
+}
+
+impl<S, T> Middleware<S::Action> for Journal<S, T>
+where
+    S: Store,
+    S::Action: Undoable + Serialize,
+    T: Serialize + Send,
+{
+    fn after(&mut self, action: &S::Action, ctx: &mut Context<'_, S::Action>) {
+        let state = (self.select)(ctx.state(self.id));
+        let record = match action.history_op() {
+            Some(_) => Record::State(&state),
+            None => Record::Action(action),
+        };
+        let appended = self.append(&record).is_ok();
+        self.written += 1;
+        // 書き込みに失敗した場合も、途中まで書かれた行を残さないよう作り直す
+        // それも失敗したら操作は止めずに次の記録で再試行する
+        if !appended || self.written >= self.compact_every {
+            let _ = self.compact(&state);
+        }
+    }
+}
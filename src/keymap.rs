@@ -0,0 +1,450 @@
+use crate::input::{Input, NamedAction};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// KeyChord: 修飾キーを含む1回のキー入力
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // 文字キーのShiftは文字自体に含まれる（'?'や'Z'）
+        // "shift-a"のように小文字にShiftを付けたら大文字にする
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            code => code,
+        };
+        let modifiers = match code {
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+
+    // "ctrl-r"、"alt-shift-up"、"space"のような表記を読む
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (modifier, len) = if lower.starts_with("ctrl-") {
+                (KeyModifiers::CONTROL, 5)
+            } else if lower.starts_with("alt-") {
+                (KeyModifiers::ALT, 4)
+            } else if lower.starts_with("shift-") {
+                (KeyModifiers::SHIFT, 6)
+            } else {
+                break;
+            };
+            // "ctrl-"の後に何もなければ'-'キーではなく不正な表記
+            if rest.len() == len {
+                return None;
+            }
+            modifiers |= modifier;
+            rest = &rest[len..];
+        }
+
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => match (rest.chars().next(), rest.chars().count()) {
+                (Some(c), 1) => KeyCode::Char(c),
+                _ => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+            },
+        };
+        Some(Self::new(code, modifiers))
+    }
+}
+
+impl From<&KeyEvent> for KeyChord {
+    fn from(key: &KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            KeyCode::BackTab => f.write_str("shift-tab"),
+            KeyCode::PageUp => f.write_str("pageup"),
+            KeyCode::PageDown => f.write_str("pagedown"),
+            code => f.write_str(&format!("{code:?}").to_ascii_lowercase()),
+        }
+    }
+}
+
+// KeySequence: "g g"のように空白で区切った連続するキー入力
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KeySequence(pub Vec<KeyChord>);
+
+impl KeySequence {
+    pub fn parse(text: &str) -> Option<Self> {
+        let chords: Option<Vec<_>> = text.split_whitespace().map(KeyChord::parse).collect();
+        chords.filter(|c| !c.is_empty()).map(Self)
+    }
+
+    fn starts_with(&self, prefix: &[KeyChord]) -> bool {
+        self.0.starts_with(prefix)
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, chord) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{chord}")?;
+        }
+        Ok(())
+    }
+}
+
+// Preset: キー表記とコマンド名の組
+// "list:up"のように部品の名前を前に付けると、その部品にフォーカスがあるときだけの割り当てになる
+pub type Preset = &'static [(&'static str, &'static str)];
+
+// KeymapConfig: 設定ファイルの[keymap]
+#[derive(Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct KeymapConfig {
+    // 元にするプリセットの名前（省略時は"default"）
+    pub preset: Option<String>,
+    // プリセットに追加・上書きするキー表記とコマンド名（"none"なら割り当てを外す）
+    // "list:up"のような部品ごとの割り当ても書ける
+    pub keys: BTreeMap<String, String>,
+}
+
+// KeymapError: キーマップを構成できなかった理由
+#[derive(Debug)]
+pub enum KeymapError {
+    UnknownPreset(String),
+    InvalidKey(String),
+    UnknownCommand { keys: String, command: String },
+    // 一方のキー列がもう一方の先頭と一致し、後者が押せない
+    Conflict { prefix: String, keys: String },
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::UnknownPreset(name) => write!(f, "unknown keymap preset: {name}"),
+            KeymapError::InvalidKey(keys) => write!(f, "invalid key: {keys:?}"),
+            KeymapError::UnknownCommand { keys, command } => {
+                write!(f, "{keys:?} is bound to unknown command {command:?}")
+            }
+            KeymapError::Conflict { prefix, keys } => {
+                write!(
+                    f,
+                    "{prefix:?} conflicts with {keys:?}: one is a prefix of the other"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+// Binding: キー列と、それが押されたときのコマンド
+pub struct Binding<A> {
+    pub keys: KeySequence,
+    // 部品の名前（その部品にフォーカスがあるときだけ有効）。Noneなら常に有効
+    pub pane: Option<String>,
+    pub name: String,
+    pub description: &'static str,
+    pub input: Input<A>,
+}
+
+// Command: 名前で参照できるコマンド（キーが割り当てられていないものも含む）
+pub struct Command<A> {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub input: Input<A>,
+}
+
+// プリセットの割り当てを外すときのコマンド名
+pub const UNBIND: &str = "none";
+
+// 回数として受け付ける最大値
+const MAX_COUNT: u32 = 9999;
+
+// Keymap: キー入力をコマンドに変換する。複数キーの列は途中まで入力を溜める
+// 割り当てのない数字は次のコマンドの回数になる（"10k"）
+pub struct Keymap<A> {
+    bindings: Vec<Binding<A>>,
+    commands: Vec<Command<A>>,
+    // 入力途中の回数
+    count: Option<u32>,
+    with_count: fn(&A, u32) -> Vec<A>,
+    pending: Vec<KeyChord>,
+    // 直前に一致したキー列の長さ
+    matched: usize,
+    // フォーカスのある部品の名前
+    pane: Option<&'static str>,
+}
+
+impl<A: NamedAction> Keymap<A> {
+    // プリセットに設定を重ねて構成し、問題があればすべて報告する
+    pub fn from_config(
+        presets: &[(&str, Preset)],
+        config: &KeymapConfig,
+    ) -> Result<Self, Vec<KeymapError>> {
+        let name = config.preset.as_deref().unwrap_or("default");
+        let Some((_, preset)) = presets.iter().find(|(n, _)| *n == name) else {
+            return Err(vec![KeymapError::UnknownPreset(name.to_owned())]);
+        };
+        let user = config.keys.iter().map(|(k, c)| (k.as_str(), c.as_str()));
+        Self::new(preset.iter().copied().chain(user))
+    }
+
+    // 同じキー列は後のものが優先される。コマンド名がUNBINDなら割り当てを外す
+    pub fn new<'a>(
+        entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self, Vec<KeymapError>> {
+        let mut errors = Vec::new();
+        let mut bindings: Vec<Binding<A>> = Vec::new();
+        for (keys, command) in entries {
+            let (pane, seq) = split_pane(keys);
+            let Some(seq) = KeySequence::parse(seq) else {
+                errors.push(KeymapError::InvalidKey(keys.to_owned()));
+                continue;
+            };
+            let pane = pane.map(str::to_owned);
+            if command == UNBIND {
+                bindings.retain(|b| b.keys != seq || b.pane != pane);
+                continue;
+            }
+            let Some(input) = Input::from_name(command) else {
+                errors.push(KeymapError::UnknownCommand {
+                    keys: keys.to_owned(),
+                    command: command.to_owned(),
+                });
+                continue;
+            };
+            bindings.retain(|b| b.keys != seq || b.pane != pane);
+            bindings.push(Binding {
+                keys: seq,
+                pane,
+                name: command.to_owned(),
+                description: input.description(),
+                input,
+            });
+        }
+
+        // 部品ごとの割り当ては全体の同じキー列を上書きできるが、先頭の一致は押し分けられない
+        for (i, a) in bindings.iter().enumerate() {
+            for b in &bindings[i + 1..] {
+                let overlaps = a.pane.is_none() || b.pane.is_none() || a.pane == b.pane;
+                if !overlaps || (a.pane != b.pane && a.keys == b.keys) {
+                    continue;
+                }
+                let (short, long) = if a.keys.0.len() <= b.keys.0.len() {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                if long.keys.starts_with(&short.keys.0) {
+                    errors.push(KeymapError::Conflict {
+                        prefix: short.keys.to_string(),
+                        keys: long.keys.to_string(),
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            let commands = Input::named()
+                .into_iter()
+                .map(|(name, input)| Command {
+                    name,
+                    description: input.description(),
+                    input,
+                })
+                .collect();
+            Ok(Self {
+                bindings,
+                commands,
+                count: None,
+                with_count: A::with_count,
+                pending: Vec::new(),
+                matched: 0,
+                pane: None,
+            })
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl<A: Clone> Keymap<A> {
+    pub fn bindings(&self) -> &[Binding<A>] {
+        &self.bindings
+    }
+
+    // フォーカスのある部品で有効な割り当て（部品の割り当てが上書きした全体の割り当ては除く）
+    pub fn active(&self) -> impl Iterator<Item = &Binding<A>> {
+        self.bindings.iter().filter(|b| match b.pane.as_deref() {
+            Some(pane) => Some(pane) == self.pane,
+            None => !self
+                .bindings
+                .iter()
+                .any(|o| o.pane.is_some() && o.pane.as_deref() == self.pane && o.keys == b.keys),
+        })
+    }
+
+    // フォーカスのある部品を設定する（部品ごとの割り当てを切り替える）
+    pub fn set_pane(&mut self, pane: Option<&'static str>) {
+        if self.pane != pane {
+            self.pane = pane;
+            self.pending.clear();
+        }
+    }
+
+    // 名前で参照できるすべてのコマンド
+    pub fn commands(&self) -> &[Command<A>] {
+        &self.commands
+    }
+
+    // コマンドに割り当てられたキー列
+    pub fn keys_for<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a KeySequence> + 'a {
+        self.active()
+            .filter(move |b| b.name == name)
+            .map(|b| &b.keys)
+    }
+
+    // 入力途中のキー列
+    pub fn pending(&self) -> &[KeyChord] {
+        &self.pending
+    }
+
+    // 入力途中の回数
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+
+    // 直前のコマンドに付いた回数を取り出す
+    pub fn take_count(&mut self) -> Option<u32> {
+        self.count.take()
+    }
+
+    // 回数を付けて実行するActionの列
+    pub fn repeat(&self, action: &A, count: u32) -> Vec<A> {
+        (self.with_count)(action, count)
+    }
+
+    // 直前にコマンドになったキー列の長さ
+    pub fn matched(&self) -> usize {
+        self.matched
+    }
+
+    // 入力イベントをコマンドに変換する。キー列の途中ならNone
+    pub fn handle(&mut self, event: &Event) -> Option<Input<A>> {
+        let Event::Key(key) = event else {
+            return None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        let chord = KeyChord::from(key);
+        if self.push_digit(chord) {
+            return None;
+        }
+        self.pending.push(chord);
+        if let Some(input) = self.resolve() {
+            return input;
+        }
+        // どのキー列にも続かなければ、今のキー単独で解釈し直す
+        self.pending = vec![chord];
+        self.resolve().unwrap_or_else(|| {
+            self.pending.clear();
+            self.count = None;
+            None
+        })
+    }
+
+    // キー列の先頭で、割り当てのない数字なら回数として溜める
+    fn push_digit(&mut self, chord: KeyChord) -> bool {
+        let KeyCode::Char(c) = chord.code else {
+            return false;
+        };
+        let Some(digit) = c.to_digit(10) else {
+            return false;
+        };
+        // 先頭の0は回数にならない
+        if !self.pending.is_empty()
+            || !chord.modifiers.is_empty()
+            || (digit == 0 && self.count.is_none())
+            || self.active().any(|b| b.keys.0[0] == chord)
+        {
+            return false;
+        }
+        let count = self.count.unwrap_or(0).saturating_mul(10) + digit;
+        self.count = Some(count.min(MAX_COUNT));
+        true
+    }
+
+    // 完全に一致すればSome(Some)、途中まで一致すればSome(None)
+    fn resolve(&mut self) -> Option<Option<Input<A>>> {
+        let first = self.active().find(|b| b.keys.starts_with(&self.pending))?;
+        if first.keys.0.len() == self.pending.len() {
+            let input = first.input.clone();
+            self.matched = self.pending.len();
+            self.pending.clear();
+            Some(Some(input))
+        } else {
+            Some(None)
+        }
+    }
+}
+
+// "list:up"を部品の名前とキー表記に分ける
+// ":"キーそのもの（":"や"ctrl-:"）は部品の名前と見なさない
+fn split_pane(keys: &str) -> (Option<&str>, &str) {
+    match keys.split_once(':') {
+        Some((pane, rest))
+            if !pane.is_empty()
+                && !rest.is_empty()
+                && pane.chars().all(|c| c.is_alphanumeric() || c == '_') =>
+        {
+            (Some(pane), rest)
+        }
+        _ => (None, keys),
+    }
+}
@@ -0,0 +1,50 @@
+// Fluxアーキテクチャによるratatuiアプリケーションの骨組み
+#[cfg(feature = "counter")]
+pub mod bigint;
+pub mod component;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "counter")]
+pub mod counter;
+pub mod debug;
+pub mod dispatcher;
+pub mod focus;
+pub mod help;
+pub mod history;
+pub mod input;
+#[cfg(feature = "persistence")]
+pub mod journal;
+pub mod keymap;
+#[cfg(feature = "scripting")]
+pub mod macros;
+pub mod middleware;
+pub mod mouse;
+pub mod palette;
+pub mod paths;
+#[cfg(feature = "persistence")]
+pub mod persist;
+pub mod prompt;
+pub mod selector;
+pub mod source;
+pub mod store;
+pub mod terminal;
+pub mod view;
+
+pub use component::{Component, Node, Split, Tree};
+pub use debug::{ActionLog, DebugPane, Recorder, Travel};
+pub use dispatcher::{DispatchError, Dispatcher, DispatcherBuilder, StoreId};
+pub use focus::Focus;
+pub use history::{History, HistoryOp, Restore, Timeline, Undoable};
+pub use input::{Input, NamedAction};
+pub use keymap::{Command, KeyChord, KeySequence, Keymap, KeymapConfig, KeymapError};
+#[cfg(feature = "scripting")]
+pub use macros::Macros;
+pub use middleware::{Context, Middleware};
+pub use mouse::Hits;
+pub use selector::{Cached, Selector};
+#[cfg(feature = "scripting")]
+pub use source::ReplayInput;
+pub use source::{InputSource, LiveInput, ScriptedInput};
+pub use store::Store;
+pub use terminal::{Signal, Signals, TerminalGuard};
+pub use view::{Render, View};
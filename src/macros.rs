@@ -0,0 +1,228 @@
+use crate::prompt::Prompt;
+use crate::source::{self, InputSource, RecordedEvent, ReplayInput};
+use crossterm::event::{Event, KeyEvent};
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::time::Instant;
+
+// Recording: 記録中のキー入力
+struct Recording {
+    events: Vec<RecordedEvent>,
+    last: Instant,
+}
+
+// Named: 名前の入力欄で確定した操作
+pub(crate) enum Named {
+    Save(String),
+    Play(String),
+}
+
+// Macros: キー入力を記録してファイルに保存し、あとで再生する
+pub struct Macros {
+    // 保存先のディレクトリ
+    dir: PathBuf,
+    // 再生速度の倍率（1.0で記録時と同じ間隔）
+    speed: f64,
+    recording: Option<Recording>,
+    playing: Option<ReplayInput>,
+    // このセッションで最後に記録したマクロ
+    last: Option<Vec<RecordedEvent>>,
+    // 保存・再生するマクロの名前の入力欄
+    prompt: Option<Prompt<Named>>,
+    // 画面に表示する直近の結果
+    notice: Option<String>,
+}
+
+impl Macros {
+    pub fn new(dir: PathBuf, speed: f64) -> Self {
+        Self {
+            dir,
+            speed,
+            recording: None,
+            playing: None,
+            last: None,
+            prompt: None,
+            notice: None,
+        }
+    }
+
+    // マクロファイルを再生し始める
+    pub fn play_file(&mut self, path: &Path) -> io::Result<()> {
+        let events = source::load(path)?;
+        self.play(events);
+        Ok(())
+    }
+
+    fn play(&mut self, events: Vec<RecordedEvent>) {
+        self.playing = Some(ReplayInput::new(events).with_speed(self.speed));
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing.is_some()
+    }
+
+    // 記録中・再生中の表示
+    pub fn status(&self) -> Option<&str> {
+        if self.is_recording() {
+            Some("● REC")
+        } else if self.is_playing() {
+            Some("▶ PLAY")
+        } else {
+            self.notice.as_deref()
+        }
+    }
+
+    // 人が入力したキーを記録する
+    pub(crate) fn record(&mut self, event: &Event) {
+        self.notice = None;
+        let (Some(recording), Event::Key(_)) = (&mut self.recording, event) else {
+            return;
+        };
+        let now = Instant::now();
+        recording.events.push(RecordedEvent {
+            delay_ms: (now - recording.last).as_millis() as u64,
+            event: event.clone(),
+        });
+        recording.last = now;
+    }
+
+    // 記録を開始する。記録中なら終了し、名前を入力して保存する
+    // trailingは記録を止めたキー列の長さで、マクロには含めない
+    // 保存や読み込みの失敗はアプリを止めずに画面に表示する
+    pub(crate) fn toggle_recording(&mut self, trailing: usize) {
+        let Some(mut recording) = self.recording.take() else {
+            self.recording = Some(Recording {
+                events: Vec::new(),
+                last: Instant::now(),
+            });
+            return;
+        };
+        let len = recording.events.len().saturating_sub(trailing);
+        recording.events.truncate(len);
+        if recording.events.is_empty() {
+            self.notice = Some("empty macro discarded".to_owned());
+            return;
+        }
+        // 保存しなくてもこのセッションでは再生できる
+        self.last = Some(recording.events);
+        let parse = |text: &str| macro_name(text).map(Named::Save);
+        self.open_prompt(Prompt::new(Box::new(parse)).with_label("save macro as: "));
+    }
+
+    // 保存したマクロを名前で選んで再生する
+    pub(crate) fn prompt_play(&mut self) {
+        let dir = self.dir.clone();
+        let parse = move |text: &str| {
+            let name = macro_name(text)?;
+            if !macro_path(&dir, &name).exists() {
+                return Err(format!("no macro named {name}"));
+            }
+            Ok(Named::Play(name))
+        };
+        self.open_prompt(Prompt::new(Box::new(parse)).with_label("play macro: "));
+    }
+
+    fn open_prompt(&mut self, mut prompt: Prompt<Named>) {
+        prompt.open();
+        self.prompt = Some(prompt);
+    }
+
+    // 名前を入力中の入力欄
+    pub(crate) fn prompt(&self) -> Option<&Prompt<Named>> {
+        self.prompt.as_ref()
+    }
+
+    // 名前の入力欄のキー入力。入力を確定したら保存か再生をする
+    pub(crate) fn handle_key(&mut self, key: &KeyEvent) {
+        let Some(prompt) = &mut self.prompt else {
+            return;
+        };
+        let named = prompt.handle(key);
+        if prompt.is_open() {
+            return;
+        }
+        self.prompt = None;
+        match named {
+            Some(Named::Save(name)) => self.save(&name),
+            Some(Named::Play(name)) => {
+                let path = macro_path(&self.dir, &name);
+                if let Err(e) = self.play_file(&path) {
+                    self.notice = Some(format!("cannot play {name}: {e}"));
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn save(&mut self, name: &str) {
+        let Some(events) = &self.last else {
+            return;
+        };
+        let path = macro_path(&self.dir, name);
+        self.notice = Some(match source::save(&path, events) {
+            Ok(()) => format!("saved {}", path.display()),
+            Err(e) => format!("cannot save {}: {e}", path.display()),
+        });
+    }
+
+    // 最後に記録したマクロを再生する（なければ保存先で最も新しいファイル）
+    pub(crate) fn play_last(&mut self) {
+        if let Some(events) = self.last.clone() {
+            self.play(events);
+            return;
+        }
+        if let Err(e) = self.play_newest() {
+            self.notice = Some(e.to_string());
+        }
+    }
+
+    fn play_newest(&mut self) -> io::Result<()> {
+        let newest = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+                .max_by_key(|e| e.metadata().and_then(|m| m.modified()).ok()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        match newest {
+            Some(entry) => self.play_file(&entry.path()),
+            None => Err(io::Error::other("no macro recorded")),
+        }
+    }
+
+    // 再生中のマクロの次のイベント。再生が終わるとNone、再生中でなければ完了しない
+    pub(crate) async fn next_event(&mut self) -> Option<io::Result<Event>> {
+        let Some(playing) = &mut self.playing else {
+            return std::future::pending().await;
+        };
+        let event = playing.next_event().await;
+        if event.is_none() {
+            self.playing = None;
+        }
+        event
+    }
+}
+
+fn macro_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{name}.jsonl"))
+}
+
+// マクロの名前はファイル名にするので、保存先の外を指せない文字だけにする
+fn macro_name(text: &str) -> Result<String, String> {
+    let name = text.trim();
+    if name.is_empty() {
+        return Err("enter a name".to_owned());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("use letters, digits, - and _".to_owned());
+    }
+    Ok(name.to_owned())
+}
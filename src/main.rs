@@ -1,132 +1,397 @@
-use crossterm::event::{self, Event, KeyCode};
-use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    widgets::{Block, Borders, Paragraph},
-    Terminal,
-};
+use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
-use std::sync::{Arc, Mutex};
-use tokio::time::Duration;
+#[cfg(any(feature = "config", feature = "persistence", feature = "scripting"))]
+use std::path::PathBuf;
+use testtui::counter::{self, CounterAction, CounterStore};
+use testtui::{
+    DebugPane, Dispatcher, History, Keymap, KeymapConfig, LiveInput, Recorder, Signals,
+    TerminalGuard, View,
+};
+#[cfg(feature = "scripting")]
+use {
+    ratatui::backend::TestBackend,
+    testtui::{Macros, ReplayInput},
+};
+#[cfg(feature = "persistence")]
+use {
+    testtui::{journal, persist, Store},
+    tokio::time::Duration,
+};
 
-// Action: 状態を変更するイベント
-enum Action {
-    Increment,
-    Decrement,
+// Undoで遡れる履歴の件数の既定値
+const HISTORY_DEPTH: usize = 100;
+// デバッグパネルに残すActionの数
+const DEBUG_LOG_DEPTH: usize = 1000;
+// 状態を自動保存する間隔
+#[cfg(feature = "persistence")]
+const AUTOSAVE_PERIOD: Duration = Duration::from_secs(30);
+// ジャーナルをcompactするまでの記録数
+#[cfg(feature = "persistence")]
+const COMPACT_EVERY: usize = 1000;
+
+const USAGE: &str = "usage: testtui [--debug] [--config PATH] [--state-file PATH] [--journal PATH]
+               [--replay MACRO] [--speed FACTOR]
+       testtui replay JOURNAL
+       testtui batch EVENTS [--config PATH]";
+// --speedで受け付ける倍率の範囲
+#[cfg(feature = "scripting")]
+const SPEED_RANGE: std::ops::RangeInclusive<f64> = 0.01..=100.0;
+// batchで描画する画面の大きさ
+#[cfg(feature = "scripting")]
+const BATCH_SIZE: (u16, u16) = (80, 24);
+
+// 設定ファイル（省略時はXDGの設定ディレクトリのconfig.toml）
+#[derive(Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+struct Config {
+    // Undoで遡れる履歴の件数
+    history_depth: Option<usize>,
+    // 1秒あたりの描画回数の上限（0なら上限なし）
+    max_fps: Option<u32>,
+    keymap: KeymapConfig,
 }
 
-// Store: アプリケーションの状態を管理
-struct Store {
-    count: i32,
+// コマンドライン引数
+#[derive(Default)]
+struct Args {
+    // Actionログを記録し、タイムトラベル用のパネルを表示する
+    debug: bool,
+    #[cfg(feature = "config")]
+    config: Option<PathBuf>,
+    // 状態を保存するファイル（省略時はXDGのデータディレクトリ）
+    #[cfg(feature = "persistence")]
+    state_file: Option<PathBuf>,
+    // Actionを追記するジャーナル（省略時はXDGのデータディレクトリ）
+    #[cfg(feature = "persistence")]
+    journal: Option<PathBuf>,
+    // 起動直後に再生するマクロ
+    #[cfg(feature = "scripting")]
+    replay: Option<PathBuf>,
+    // マクロの再生速度の倍率
+    #[cfg(feature = "scripting")]
+    speed: Option<f64>,
 }
 
-impl Store {
-    fn new() -> Self {
-        Self { count: 0 }
-    }
+// サブコマンド
+enum Command {
+    Run(Args),
+    // ジャーナルを画面なしで再生し、最終状態を表示する
+    #[cfg(feature = "persistence")]
+    Replay(PathBuf),
+    // 記録した入力イベントを画面なしで流し、最後の画面を表示する
+    #[cfg(feature = "scripting")]
+    Batch(Args, PathBuf),
+}
 
-    // Actionを受けて状態を更新する
-    fn update(&mut self, action: Action) {
-        self.count += match action {
-            Action::Increment => 1,
-            Action::Decrement => -1,
+// 値を取る引数がすべて無効な構成ではiter.next()が1箇所になる
+#[cfg_attr(
+    not(any(feature = "config", feature = "persistence", feature = "scripting")),
+    allow(clippy::while_let_on_iterator)
+)]
+fn parse_args() -> Result<Command, String> {
+    let mut args = Args::default();
+    let mut iter = std::env::args().skip(1).peekable();
+    #[cfg(feature = "persistence")]
+    if iter.peek().map(String::as_str) == Some("replay") {
+        iter.next();
+        let path = iter.next().ok_or("replay requires a journal path")?;
+        return match iter.next() {
+            Some(arg) => Err(format!("unexpected argument: {arg}")),
+            None => Ok(Command::Replay(path.into())),
         };
     }
+    #[cfg(feature = "scripting")]
+    let batch = match iter.peek().map(String::as_str) {
+        Some("batch") => {
+            iter.next();
+            Some(iter.next().ok_or("batch requires an events file")?)
+        }
+        _ => None,
+    };
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--debug" => args.debug = true,
+            #[cfg(feature = "config")]
+            "--config" => {
+                let path = iter.next().ok_or("--config requires a path")?;
+                args.config = Some(path.into());
+            }
+            #[cfg(feature = "persistence")]
+            "--state-file" => {
+                let path = iter.next().ok_or("--state-file requires a path")?;
+                args.state_file = Some(path.into());
+            }
+            #[cfg(feature = "persistence")]
+            "--journal" => {
+                let path = iter.next().ok_or("--journal requires a path")?;
+                args.journal = Some(path.into());
+            }
+            #[cfg(feature = "scripting")]
+            "--replay" => {
+                let path = iter.next().ok_or("--replay requires a macro file")?;
+                args.replay = Some(path.into());
+            }
+            #[cfg(feature = "scripting")]
+            "--speed" => {
+                let speed = iter.next().ok_or("--speed requires a factor")?;
+                match speed.parse::<f64>() {
+                    Ok(speed) if SPEED_RANGE.contains(&speed) => args.speed = Some(speed),
+                    _ => {
+                        return Err(format!(
+                            "invalid speed: {speed} (expected {} to {})",
+                            SPEED_RANGE.start(),
+                            SPEED_RANGE.end()
+                        ))
+                    }
+                }
+            }
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+    #[cfg(feature = "scripting")]
+    if let Some(path) = batch {
+        return Ok(Command::Batch(args, path.into()));
+    }
+    Ok(Command::Run(args))
 }
 
-// Dispatcher: ActionをStoreに通知
-struct Dispatcher {
-    store: Arc<Mutex<Store>>,
+#[tokio::main]
+async fn main() -> Result<(), io::Error> {
+    match parse_args() {
+        Ok(Command::Run(args)) => run(args).await,
+        #[cfg(feature = "persistence")]
+        Ok(Command::Replay(path)) => {
+            if let Err(e) = replay(&path) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        #[cfg(feature = "scripting")]
+        Ok(Command::Batch(args, path)) => batch(args, &path).await,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            std::process::exit(2);
+        }
+    }
 }
 
-impl Dispatcher {
-    fn new(store: Arc<Mutex<Store>>) -> Self {
-        Self { store }
+#[cfg(feature = "persistence")]
+fn replay(path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut store = CounterStore::new();
+    let replay = journal::replay(path, &mut store)?
+        .ok_or_else(|| format!("{} does not exist", path.display()))?;
+    if replay.torn {
+        eprintln!("warning: ignored a torn record at the end of the journal");
     }
+    println!("{}", serde_json::to_string_pretty(store.state())?);
+    Ok(())
+}
+
+#[cfg(feature = "scripting")]
+async fn batch(args: Args, path: &std::path::Path) -> io::Result<()> {
+    let (config, keymap) = match load_config(&args) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+    let input = ReplayInput::open(path)?;
 
-    // ActionをDispatcher経由でStoreに送信
-    fn dispatch(&self, action: Action) {
-        self.store.lock().unwrap().update(action);
+    let (dispatcher, state) = Dispatcher::spawn(History::new(
+        CounterStore::new(),
+        config.history_depth.unwrap_or(HISTORY_DEPTH),
+    ));
+    let (width, height) = BATCH_SIZE;
+    let terminal = Terminal::new(TestBackend::new(width, height))?;
+    let mut view = View::new(
+        terminal,
+        Box::new(input),
+        dispatcher,
+        state,
+        counter::view(),
+        keymap,
+    )
+    .with_prompt(Box::new(counter::parse));
+    view.run().await?;
+    view.draw()?;
+
+    let buffer = view.terminal().backend().buffer();
+    for y in 0..height {
+        let line: String = (0..width).map(|x| buffer[(x, y)].symbol()).collect();
+        println!("{}", line.trim_end());
     }
+    Ok(())
 }
 
-// View: ユーザーインターフェースを描画し、ユーザー操作に反応
-struct View<B: ratatui::backend::Backend> {
-    terminal: Terminal<B>,
-    dispatcher: Dispatcher,
+// 設定ファイルを読み、キーマップを構成する
+fn load_config(args: &Args) -> Result<(Config, Keymap<CounterAction>), String> {
+    #[cfg(feature = "config")]
+    let config: Config = match args
+        .config
+        .clone()
+        .or_else(|| testtui::paths::config_dir("testtui").map(|dir| dir.join("config.toml")))
+    {
+        Some(path) => testtui::config::load(&path).map_err(|e| e.to_string())?,
+        None => Config::default(),
+    };
+    #[cfg(not(feature = "config"))]
+    let config = {
+        let _ = args;
+        Config::default()
+    };
+
+    let keymap = Keymap::from_config(counter::PRESETS, &config.keymap).map_err(|errors| {
+        let lines: Vec<_> = errors.iter().map(|e| format!("keymap: {e}")).collect();
+        lines.join("\n")
+    })?;
+    Ok((config, keymap))
 }
 
-impl<B: ratatui::backend::Backend> View<B> {
-    fn new(terminal: Terminal<B>, dispatcher: Dispatcher) -> Self {
-        Self {
-            terminal,
-            dispatcher,
+async fn run(args: Args) -> io::Result<()> {
+    let (config, keymap) = match load_config(&args) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
         }
-    }
-
-    fn draw_ui(&mut self, count: i32) -> io::Result<()> {
-        self.terminal.draw(|f| {
-            // レイアウト設定（垂直方向に1つの大きな領域）
-            let chunk = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(100)].as_ref())
-                .split(f.area())[0]; // 1つの領域を分割
-
-            // 「Counter: x」というテキストを表示するパラグラフ
-            f.render_widget(
-                Paragraph::new(format!("Counter: {}", count))
-                    .block(Block::default().borders(Borders::ALL)),
-                chunk,
-            );
-        })?;
-        Ok(())
-    }
+    };
 
-    async fn run(&mut self) -> io::Result<()> {
-        loop {
-            let count = self.dispatcher.store.lock().unwrap().count;
-            self.draw_ui(count)?;
-
-            if let Some(action) = self.handle_user_input().await {
-                self.dispatcher.dispatch(action);
+    // 保存された状態を読み込む。読めなければ既定の状態から始める
+    #[cfg(feature = "persistence")]
+    let data_dir = testtui::paths::data_dir("testtui");
+    #[cfg(feature = "persistence")]
+    let state_file = args
+        .state_file
+        .clone()
+        .or_else(|| data_dir.as_ref().map(|dir| dir.join("state.json")));
+    #[cfg(feature = "persistence")]
+    let journal_file = args
+        .journal
+        .clone()
+        .or_else(|| data_dir.as_ref().map(|dir| dir.join("journal.jsonl")));
+    #[cfg(feature = "persistence")]
+    let store = {
+        let mut store = match state_file.as_deref().map(persist::load) {
+            Some(Ok(Some(state))) => CounterStore::with_state(state),
+            Some(Err(e)) => {
+                eprintln!("warning: {e}; starting from the default state");
+                CounterStore::new()
             }
-
-            if event::poll(Duration::from_millis(50))? {
-                if let Event::Key(key) = event::read()? {
-                    if key.code == KeyCode::Char('q') {
-                        break;
+            _ => CounterStore::new(),
+        };
+        // ジャーナルがあれば状態ファイルより新しいので、それを再生する
+        if let Some(path) = &journal_file {
+            match journal::replay(path, &mut store) {
+                Ok(Some(replay)) if replay.torn => {
+                    eprintln!("warning: ignored a torn record at the end of the journal")
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("warning: {e}; starting from the saved state");
+                    // 新しいジャーナルで上書きする前に、壊れていない記録ごと残しておく
+                    if let journal::JournalError::Corrupt { .. } = e {
+                        let aside = journal::set_aside(path)?;
+                        eprintln!("warning: moved the journal to {}", aside.display());
                     }
+                    store = CounterStore::with_state(
+                        state_file
+                            .as_deref()
+                            .and_then(|path| persist::load(path).ok().flatten())
+                            .unwrap_or_default(),
+                    );
                 }
             }
         }
-        self.terminal.clear()?;
-        Ok(())
-    }
-
-    async fn handle_user_input(&self) -> Option<Action> {
-        if event::poll(Duration::from_millis(50)).unwrap() {
-            if let Event::Key(key) = event::read().unwrap() {
-                return match key.code {
-                    KeyCode::Up => Some(Action::Increment),
-                    KeyCode::Down => Some(Action::Decrement),
-                    _ => None,
-                };
-            }
-        }
-        None
-    }
-}
+        store
+    };
+    #[cfg(not(feature = "persistence"))]
+    let store = CounterStore::new();
+    #[cfg(feature = "persistence")]
+    let initial = store.state().clone();
 
-#[tokio::main]
-async fn main() -> Result<(), io::Error> {
+    // ここから先はどの経路で抜けても端末が元に戻る
+    let guard = TerminalGuard::enter()?;
     let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    let store = Arc::new(Mutex::new(Store::new()));
-    let dispatcher = Dispatcher::new(store.clone());
+    let mut builder = Dispatcher::builder();
+    let (id, state) = builder.register(History::new(
+        store,
+        config.history_depth.unwrap_or(HISTORY_DEPTH),
+    ));
+    let log = args.debug.then(|| {
+        let (recorder, log) = Recorder::new(id, DEBUG_LOG_DEPTH);
+        builder.middleware(recorder);
+        log
+    });
+    #[cfg(feature = "persistence")]
+    if let Some(path) = &journal_file {
+        builder.middleware(journal::Journal::open(
+            path.clone(),
+            id,
+            &initial,
+            |t| t.current.clone(),
+            COMPACT_EVERY,
+        )?);
+    }
+    let dispatcher = builder.spawn();
+
+    #[cfg(feature = "persistence")]
+    let autosave = state_file.as_ref().map(|path| {
+        persist::autosave(state.clone(), path.clone(), AUTOSAVE_PERIOD, |t| {
+            t.current.clone()
+        })
+    });
 
-    let mut view = View::new(terminal, dispatcher);
+    let mut view = View::new(
+        terminal,
+        Box::new(LiveInput::new()),
+        dispatcher,
+        state.clone(),
+        counter::view(),
+        keymap,
+    );
+    view = view
+        .with_signals(Signals::new()?)
+        .with_prompt(Box::new(counter::parse))
+        .with_animation(counter::RATE_TICK);
+    if let Some(fps) = config.max_fps {
+        view = view.with_max_fps(fps);
+    }
+    if let Some(log) = log {
+        view = view.with_debug(DebugPane::new(log));
+    }
+    #[cfg(feature = "scripting")]
+    {
+        let dir = testtui::paths::data_dir("testtui")
+            .map_or_else(|| PathBuf::from("macros"), |dir| dir.join("macros"));
+        let mut macros = Macros::new(dir, args.speed.unwrap_or(1.0));
+        if let Some(path) = &args.replay {
+            macros.play_file(path)?;
+        }
+        view = view.with_macros(macros);
+    }
     view.run().await?;
+    drop(guard);
+
+    // 終了時の状態を保存する
+    // 自動保存が同じファイルを書いている途中かもしれないので、止めて終わるのを待つ
+    #[cfg(feature = "persistence")]
+    if let Some(autosave) = autosave {
+        autosave.abort();
+        let _ = autosave.await;
+    }
+    #[cfg(feature = "persistence")]
+    if let Some(path) = &state_file {
+        let current = state.borrow().current.clone();
+        persist::save(path, &current)?;
+    }
     Ok(())
 }
@@ -0,0 +1,47 @@
+use crate::dispatcher::{AnyStore, StoreId};
+use crate::store::Store;
+
+// Middleware: Dispatcherを通るActionの前後に処理を差し込む
+// beforeは登録順、afterは逆順に呼ばれる
+pub trait Middleware<A>: Send {
+    // Storeに届く前に呼ばれる。Actionを書き換えて返すか、Noneで中断する
+    fn before(&mut self, action: A, _ctx: &mut Context<'_, A>) -> Option<A> {
+        Some(action)
+    }
+
+    // すべてのStoreが更新された後に呼ばれる
+    fn after(&mut self, _action: &A, _ctx: &mut Context<'_, A>) {}
+}
+
+// Context: Middlewareから見たStoreの状態と後続Actionの発行口
+pub struct Context<'a, A> {
+    stores: &'a [Box<dyn AnyStore<A>>],
+    emitted: &'a mut Vec<A>,
+}
+
+impl<'a, A> Context<'a, A> {
+    pub(crate) fn new(stores: &'a [Box<dyn AnyStore<A>>], emitted: &'a mut Vec<A>) -> Self {
+        Self { stores, emitted }
+    }
+
+    // 現在の状態（beforeでは適用前、afterでは適用後）
+    pub fn state<S: Store>(&self, id: StoreId<S>) -> &S::State {
+        self.stores[id.index]
+            .state()
+            .downcast_ref()
+            .expect("StoreId belongs to another dispatcher")
+    }
+
+    // このActionが適用される前の状態
+    pub fn previous<S: Store>(&self, id: StoreId<S>) -> S::State {
+        *self.stores[id.index]
+            .previous()
+            .downcast()
+            .expect("StoreId belongs to another dispatcher")
+    }
+
+    // 現在のActionの直後に処理される後続Actionを発行する
+    pub fn dispatch(&mut self, action: A) {
+        self.emitted.push(action);
+    }
+}
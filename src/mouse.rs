@@ -0,0 +1,60 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+// 領域に割り当てたマウス操作
+enum Target<A> {
+    // 左クリック
+    Click(A),
+    // ホイール（上、下）
+    Scroll(A, A),
+}
+
+// Hits: 描画時に記録した領域とActionの対応。マウスイベントの当たり判定に使う
+pub struct Hits<A> {
+    regions: Vec<(Rect, Target<A>)>,
+}
+
+impl<A> Default for Hits<A> {
+    fn default() -> Self {
+        Self {
+            regions: Vec::new(),
+        }
+    }
+}
+
+impl<A: Clone> Hits<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // areaを左クリックしたらactionを発行する
+    pub fn click(&mut self, area: Rect, action: A) {
+        self.regions.push((area, Target::Click(action)));
+    }
+
+    // area上でホイールを回したらup/downを発行する
+    pub fn scroll(&mut self, area: Rect, up: A, down: A) {
+        self.regions.push((area, Target::Scroll(up, down)));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    // マウスイベントに対応するAction。重なっている場合は後から記録した（手前の）領域を優先する
+    pub fn hit(&self, event: &MouseEvent) -> Option<A> {
+        let position = Position::new(event.column, event.row);
+        self.regions
+            .iter()
+            .rev()
+            .filter(|(area, _)| area.contains(position))
+            .find_map(|(_, target)| match (target, event.kind) {
+                (Target::Click(action), MouseEventKind::Down(MouseButton::Left)) => {
+                    Some(action.clone())
+                }
+                (Target::Scroll(up, _), MouseEventKind::ScrollUp) => Some(up.clone()),
+                (Target::Scroll(_, down), MouseEventKind::ScrollDown) => Some(down.clone()),
+                _ => None,
+            })
+    }
+}
@@ -0,0 +1,159 @@
+use crate::input::Input;
+use crate::keymap::{Command, Keymap};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+// 一度に表示する候補の数
+const VISIBLE: usize = 10;
+
+// Palette: コマンドを名前と説明であいまい検索して実行する
+#[derive(Default)]
+pub struct Palette {
+    query: String,
+    selected: usize,
+}
+
+// Outcome: パレットでキーを処理した結果
+pub enum Outcome<A> {
+    // 開いたまま
+    Open,
+    // 何もせずに閉じた
+    Closed,
+    // 選んだコマンドを実行する
+    Run(Input<A>),
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    // 検索語に一致するコマンドを一致度の高い順に返す
+    pub fn matches<'a, A>(&self, commands: &'a [Command<A>]) -> Vec<&'a Command<A>> {
+        let mut scored: Vec<_> = commands
+            .iter()
+            .filter_map(|c| {
+                // 名前での一致を説明での一致より優先する
+                let score = fuzzy(&self.query, c.name)
+                    .or_else(|| fuzzy(&self.query, c.description).map(|s| s + 1000))?;
+                Some((score, c))
+            })
+            .collect();
+        // 同じ一致度なら登録順（安定ソート）
+        scored.sort_by_key(|(score, _)| *score);
+        scored.into_iter().map(|(_, c)| c).collect()
+    }
+
+    pub fn handle<A: Clone>(&mut self, key: &KeyEvent, keymap: &Keymap<A>) -> Outcome<A> {
+        if key.kind != KeyEventKind::Press {
+            return Outcome::Open;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Outcome::Closed,
+            KeyCode::Char('c' | 'g') if ctrl => return Outcome::Closed,
+            KeyCode::Enter => {
+                let matches = self.matches(keymap.commands());
+                return match matches.get(self.selected) {
+                    Some(command) => Outcome::Run(command.input.clone()),
+                    None => Outcome::Closed,
+                };
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => self.selected += 1,
+            KeyCode::Char('n') if ctrl => self.selected += 1,
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.selected = 0;
+            }
+            _ => {}
+        }
+        let len = self.matches(keymap.commands()).len();
+        self.selected = self.selected.min(len.saturating_sub(1));
+        Outcome::Open
+    }
+
+    // 画面上部の中央に検索欄と候補を重ねて表示する
+    pub fn render<A: Clone>(&self, f: &mut Frame, area: Rect, keymap: &Keymap<A>) {
+        let matches = self.matches(keymap.commands());
+        let rows = matches.len().clamp(1, VISIBLE);
+        let height = u16::try_from(rows + 3).unwrap_or(u16::MAX);
+        let [popup] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [_, popup] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(height)]).areas(popup);
+
+        let block = Block::default().title("Command").borders(Borders::ALL);
+        let inner = block.inner(popup);
+        f.render_widget(Clear, popup);
+        f.render_widget(block, popup);
+
+        let [input, list] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+        f.render_widget(Paragraph::new(format!("> {}", self.query)), input);
+        if matches.is_empty() {
+            f.render_widget(Paragraph::new("no matching command"), list);
+            return;
+        }
+
+        let keys: Vec<_> = matches
+            .iter()
+            .map(|c| {
+                let keys: Vec<_> = keymap.keys_for(c.name).map(|k| k.to_string()).collect();
+                keys.join(", ")
+            })
+            .collect();
+        let name_width = matches.iter().map(|c| c.name.len()).max().unwrap_or(0);
+        let key_width = keys.iter().map(|k| k.chars().count()).max().unwrap_or(0);
+        let items: Vec<_> = matches
+            .iter()
+            .zip(&keys)
+            .map(|(c, keys)| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{:<name_width$}  ", c.name)),
+                    Span::styled(
+                        format!("{keys:<key_width$}  "),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(c.description),
+                ]))
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(
+            List::new(items).highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            list,
+            &mut state,
+        );
+    }
+}
+
+// 検索語の文字が順に現れればSome（小さいほどよく一致している）
+// 先頭からの位置と、一致した文字の間の隙間を減点する
+fn fuzzy(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    for (i, q) in query.to_lowercase().chars().enumerate() {
+        let found = next + text[next..].iter().position(|&c| c == q)?;
+        score += if i == 0 { found } else { found - next };
+        next = found + 1;
+    }
+    Some(score)
+}
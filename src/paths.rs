@@ -0,0 +1,22 @@
+use std::env;
+use std::path::PathBuf;
+
+// XDGのディレクトリ（環境変数が空か未設定ならHOME配下の既定の場所）
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    match env::var_os(var) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => env::var_os("HOME")
+            .filter(|home| !home.is_empty())
+            .map(|home| PathBuf::from(home).join(fallback)),
+    }
+}
+
+// アプリケーションのデータディレクトリ（$XDG_DATA_HOME、なければ~/.local/share）
+pub fn data_dir(app: &str) -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share").map(|dir| dir.join(app))
+}
+
+// アプリケーションの設定ディレクトリ（$XDG_CONFIG_HOME、なければ~/.config）
+pub fn config_dir(app: &str) -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join(app))
+}
@@ -0,0 +1,101 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::watch;
+use tokio::time::Duration;
+
+// PersistError: 状態ファイルを読めなかった理由
+#[derive(Debug)]
+pub enum PersistError {
+    Io(PathBuf, io::Error),
+    Corrupt(PathBuf, serde_json::Error),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            PersistError::Corrupt(path, e) => {
+                write!(f, "{} is not a valid state file: {}", path.display(), e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PersistError {}
+
+// 状態を読み込む（ファイルがなければNone）
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, PersistError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(PersistError::Io(path.to_owned(), e)),
+    };
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| PersistError::Corrupt(path.to_owned(), e))
+}
+
+// 状態を書き込む。一時ファイルに書いてから置き換えるので途中で落ちても壊れない
+pub fn save<T: Serialize>(path: &Path, state: &T) -> io::Result<()> {
+    replace(path, |file| {
+        serde_json::to_writer_pretty(&mut *file, state)?;
+        file.write_all(b"\n")
+    })
+}
+
+// writeで一時ファイルに書き、ディスクに届いてからpathと置き換える
+pub(crate) fn replace(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> io::Result<()>,
+) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = tmp_path(path);
+    let mut file = fs::File::create(&tmp)?;
+    write(&mut file)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+// 書き込み途中の一時ファイルの名前（拡張子だけを替えると別のファイルと衝突する）
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+// 状態が変わっていればperiodごとに保存するタスクを起動する
+// selectで状態から保存する部分を取り出す
+// 終了時に保存する前には、返したハンドルでタスクを止めて終わるのを待つこと
+pub fn autosave<T, U, F>(
+    mut state: watch::Receiver<T>,
+    path: PathBuf,
+    period: Duration,
+    select: F,
+) -> tokio::task::JoinHandle<()>
+where
+    T: Send + Sync + 'static,
+    U: Serialize,
+    F: Fn(&T) -> U + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match state.has_changed() {
+                Ok(true) => {
+                    let snapshot = select(&state.borrow_and_update());
+                    // 保存に失敗しても次の周期で再試行する
+                    let _ = save(&path, &snapshot);
+                }
+                Ok(false) => {}
+                // Dispatcherが終了した
+                Err(_) => break,
+            }
+        }
+    })
+}
@@ -0,0 +1,103 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+// 入力欄の文字列をActionに変換する関数。失敗したら画面に表示するメッセージを返す
+pub type Parse<A> = Box<dyn Fn(&str) -> Result<A, String>>;
+
+// Prompt: 値や式を1行で入力してActionにする
+pub struct Prompt<A> {
+    parse: Parse<A>,
+    // 入力欄の先頭に表示する文字列
+    label: &'static str,
+    // 入力中ならSome
+    line: Option<Editing>,
+}
+
+// 入力中の文字列と直前の検証エラー
+#[derive(Default)]
+struct Editing {
+    text: String,
+    error: Option<String>,
+}
+
+impl<A> Prompt<A> {
+    pub fn new(parse: Parse<A>) -> Self {
+        Self {
+            parse,
+            label: "= ",
+            line: None,
+        }
+    }
+
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.label = label;
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.line.is_some()
+    }
+
+    pub fn open(&mut self) {
+        self.line = Some(Editing::default());
+    }
+
+    // キー入力を処理し、確定した入力が正しければActionを返す
+    // 正しくなければ入力欄を開いたままエラーを表示する
+    pub fn handle(&mut self, key: &KeyEvent) -> Option<A> {
+        let line = self.line.as_mut()?;
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.line = None,
+            KeyCode::Char('c' | 'g') if ctrl => self.line = None,
+            KeyCode::Enter => match (self.parse)(&line.text) {
+                Ok(action) => {
+                    self.line = None;
+                    return Some(action);
+                }
+                Err(e) => line.error = Some(e),
+            },
+            KeyCode::Backspace => {
+                line.text.pop();
+                line.error = None;
+            }
+            KeyCode::Char('u') if ctrl => {
+                line.text.clear();
+                line.error = None;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                line.text.push(c);
+                line.error = None;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    // 入力欄を1行で描画し、カーソルを入力位置に置く
+    pub fn render(&self, f: &mut Frame, area: Rect) {
+        let Some(line) = &self.line else {
+            return;
+        };
+        let mut spans = vec![Span::raw(self.label), Span::raw(line.text.as_str())];
+        if let Some(error) = &line.error {
+            spans.push(Span::styled(
+                format!("  {error}"),
+                Style::default().fg(Color::Red),
+            ));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)), area);
+        let label = u16::try_from(self.label.chars().count()).unwrap_or(u16::MAX);
+        let x = area.x + label + u16::try_from(line.text.chars().count()).unwrap_or(u16::MAX);
+        f.set_cursor_position((x.min(area.right().saturating_sub(1)), area.y));
+    }
+}
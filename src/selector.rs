@@ -0,0 +1,110 @@
+use ratatui::{buffer::Buffer, layout::Rect};
+use tokio::sync::watch;
+
+// Selector: 状態から派生値を計算する。入力が前回と同じなら計算し直さない
+// inputは状態から計算に使う部分を取り出し（安価であること）、computeが派生値を計算する
+pub struct Selector<T, I, O> {
+    input: Box<dyn Fn(&T) -> I>,
+    compute: Box<dyn FnMut(&I) -> O>,
+    cache: Option<(I, O)>,
+    version: u64,
+}
+
+impl<T, I: PartialEq, O> Selector<T, I, O> {
+    pub fn new(input: impl Fn(&T) -> I + 'static, compute: impl FnMut(&I) -> O + 'static) -> Self {
+        Self {
+            input: Box::new(input),
+            compute: Box::new(compute),
+            cache: None,
+            version: 0,
+        }
+    }
+
+    pub fn select(&mut self, state: &T) -> &O {
+        let input = (self.input)(state);
+        let fresh = self.cache.as_ref().is_some_and(|(prev, _)| *prev == input);
+        if !fresh {
+            let output = (self.compute)(&input);
+            self.cache = Some((input, output));
+            self.version += 1;
+        }
+        &self.cache.as_ref().unwrap().1
+    }
+
+    // 派生値を計算し直した回数（描画のキャッシュのキーに使う）
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+// 状態の通知から派生値を取り出し、派生値が変わったときだけ通知する
+// Viewの外（バックグラウンドのタスクなど）で状態の一部だけを購読するのに使う
+pub fn subscribe<T, O>(
+    mut state: watch::Receiver<T>,
+    mut select: impl FnMut(&T) -> O + Send + 'static,
+) -> watch::Receiver<O>
+where
+    T: Send + Sync + 'static,
+    O: PartialEq + Send + Sync + 'static,
+{
+    let (tx, rx) = watch::channel(select(&state.borrow_and_update()));
+    tokio::spawn(async move {
+        while state.changed().await.is_ok() {
+            let value = select(&state.borrow_and_update());
+            tx.send_if_modified(|current| {
+                let changed = *current != value;
+                if changed {
+                    *current = value;
+                }
+                changed
+            });
+            // 受け取る側がいなくなったら終わる
+            if tx.is_closed() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+// Cached: キーと領域が前回と同じなら、前回描画した内容を写すだけにする
+// キーには描画に使う派生値や、そのSelectorのversionを使う
+pub struct Cached<K> {
+    key: Option<(K, Rect)>,
+    buffer: Buffer,
+}
+
+impl<K> Default for Cached<K> {
+    fn default() -> Self {
+        Self {
+            key: None,
+            buffer: Buffer::empty(Rect::default()),
+        }
+    }
+}
+
+impl<K: PartialEq> Cached<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn render(
+        &mut self,
+        buf: &mut Buffer,
+        area: Rect,
+        key: K,
+        draw: impl FnOnce(Rect, &mut Buffer),
+    ) {
+        let fresh = matches!(&self.key, Some((k, a)) if *k == key && *a == area);
+        if !fresh {
+            let mut buffer = Buffer::empty(area);
+            draw(area, &mut buffer);
+            self.buffer = buffer;
+            self.key = Some((key, area));
+        }
+        let area = area.intersection(buf.area);
+        for position in area.positions() {
+            buf[position] = self.buffer[position].clone();
+        }
+    }
+}
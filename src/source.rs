@@ -0,0 +1,184 @@
+use crossterm::event::{Event, EventStream};
+use futures::future::LocalBoxFuture;
+use futures::{FutureExt, StreamExt};
+use std::collections::VecDeque;
+use std::io;
+
+// InputSource: Viewが読む入力イベントの供給元
+pub trait InputSource {
+    // 次の入力イベント。Noneなら入力の終わり
+    // select!で他の分岐が先に完了すると途中で破棄されるので、
+    // 完了する前に破棄されてもイベントを失ってはいけない
+    fn next_event(&mut self) -> LocalBoxFuture<'_, Option<io::Result<Event>>>;
+
+    // 人が操作しているか。falseならViewは入力ごとにActionの適用を待つので、
+    // 結果が実行のたびに変わらない
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
+
+// LiveInput: 端末からの入力（crossterm）
+pub struct LiveInput {
+    events: EventStream,
+}
+
+impl LiveInput {
+    pub fn new() -> Self {
+        Self {
+            events: EventStream::new(),
+        }
+    }
+}
+
+impl Default for LiveInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputSource for LiveInput {
+    fn next_event(&mut self) -> LocalBoxFuture<'_, Option<io::Result<Event>>> {
+        self.events.next().boxed_local()
+    }
+}
+
+// ScriptedInput: あらかじめ用意したイベント列
+pub struct ScriptedInput {
+    events: VecDeque<Event>,
+}
+
+impl ScriptedInput {
+    pub fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn next_event(&mut self) -> LocalBoxFuture<'_, Option<io::Result<Event>>> {
+        async move { self.events.pop_front().map(Ok) }.boxed_local()
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "scripting")]
+pub use replay::{load, save, RecordedEvent, ReplayInput};
+
+#[cfg(feature = "scripting")]
+mod replay {
+    use super::InputSource;
+    use crossterm::event::Event;
+    use futures::future::LocalBoxFuture;
+    use futures::FutureExt;
+    use serde::{Deserialize, Serialize};
+    use std::collections::VecDeque;
+    use std::io;
+    use std::path::Path;
+    use tokio::time::{Duration, Instant};
+
+    // 再生時に1つのイベントを待つ時間の上限
+    const MAX_DELAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    // RecordedEvent: 記録ファイルの1行（前のイベントからの待ち時間とイベント）
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct RecordedEvent {
+        #[serde(default)]
+        pub delay_ms: u64,
+        pub event: Event,
+    }
+
+    // JSON Linesの記録ファイルを読む
+    pub fn load(path: &Path) -> io::Result<Vec<RecordedEvent>> {
+        let text = std::fs::read_to_string(path)?;
+        let mut events = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {}", path.display(), i + 1, e),
+                )
+            })?;
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    // 記録ファイルを書く
+    pub fn save(path: &Path, events: &[RecordedEvent]) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut text = String::new();
+        for event in events {
+            text.push_str(&serde_json::to_string(event)?);
+            text.push('\n');
+        }
+        std::fs::write(path, text)
+    }
+
+    // ReplayInput: 記録ファイルのイベントを順に再生する
+    pub struct ReplayInput {
+        events: VecDeque<RecordedEvent>,
+        // 再生速度の倍率（Noneなら待たずに流す）
+        speed: Option<f64>,
+        // 待ち時間の途中で破棄されたときに再開するイベントと時刻
+        waiting: Option<(Event, Instant)>,
+    }
+
+    impl ReplayInput {
+        pub fn new(events: impl IntoIterator<Item = RecordedEvent>) -> Self {
+            Self {
+                events: events.into_iter().collect(),
+                speed: None,
+                waiting: None,
+            }
+        }
+
+        // JSON Linesの記録ファイルを読む。既定では待ち時間なしで再生する
+        pub fn open(path: &Path) -> io::Result<Self> {
+            load(path).map(Self::new)
+        }
+
+        // 記録時の間隔をspeed倍の速さで再現する
+        pub fn with_speed(mut self, speed: f64) -> Self {
+            self.speed = Some(speed);
+            self
+        }
+    }
+
+    impl InputSource for ReplayInput {
+        fn next_event(&mut self) -> LocalBoxFuture<'_, Option<io::Result<Event>>> {
+            async move {
+                if self.waiting.is_none() {
+                    let next = self.events.pop_front()?;
+                    let delay = match self.speed.filter(|s| *s > 0.0) {
+                        // 倍率が極端に小さくても待ち時間を上限で止め、溢れさせない
+                        Some(speed) => Duration::try_from_secs_f64(
+                            Duration::from_millis(next.delay_ms).as_secs_f64() / speed,
+                        )
+                        .map_or(MAX_DELAY, |delay| delay.min(MAX_DELAY)),
+                        None => Duration::ZERO,
+                    };
+                    self.waiting = Some((next.event, Instant::now() + delay));
+                }
+                let (_, at) = self.waiting.as_ref()?;
+                tokio::time::sleep_until(*at).await;
+                self.waiting.take().map(|(event, _)| Ok(event))
+            }
+            .boxed_local()
+        }
+
+        // 記録時の間隔を再現するときは人の操作として扱う
+        fn is_interactive(&self) -> bool {
+            self.speed.is_some()
+        }
+    }
+}
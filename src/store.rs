@@ -0,0 +1,12 @@
+// Store: 状態とそれを更新するReducerの抽象
+pub trait Store: Send + 'static {
+    // Viewに公開する状態のスナップショット
+    type State: Clone + Send + Sync + 'static;
+    // 状態を変更するイベント
+    type Action: Send + 'static;
+
+    fn state(&self) -> &Self::State;
+
+    // Actionを受けて状態を更新する
+    fn reduce(&mut self, action: &Self::Action);
+}
@@ -0,0 +1,124 @@
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::io;
+use std::sync::Once;
+
+// TerminalGuard: 生存中はrawモードと代替画面、マウス入力を有効にし、破棄時に必ず元に戻す
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    pub fn enter() -> io::Result<Self> {
+        install_panic_hook();
+        setup()?;
+        Ok(Self { _private: () })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
+
+fn setup() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+}
+
+// 端末を元に戻す。途中で失敗しても残りの手順は実行する
+pub fn restore() -> io::Result<()> {
+    let raw = disable_raw_mode();
+    let screen = execute!(
+        io::stdout(),
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+        cursor::Show
+    );
+    raw.and(screen)
+}
+
+// panicのメッセージが壊れた画面に埋もれないよう、先に端末を戻す
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            previous(info);
+        }));
+    });
+}
+
+// 端末を戻してからプロセスを停止し、再開したら設定し直す
+pub fn suspend() -> io::Result<()> {
+    restore()?;
+    #[cfg(unix)]
+    // SAFETY: raiseは自プロセスにシグナルを送るだけ
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+    setup()
+}
+
+// Signal: Viewが反応するシグナル
+pub enum Signal {
+    // SIGINT/SIGTERM
+    Quit,
+    // SIGTSTP
+    Suspend,
+    // SIGCONT（外部から停止・再開された）
+    Resume,
+}
+
+// Signals: プロセスに届いたシグナルを非同期に受け取る
+pub struct Signals {
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    stop: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    resume: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    // SIGTSTPを登録すると既定の停止動作は行われなくなる（suspendで停止する）
+    pub fn new() -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            Ok(Self {
+                interrupt: signal(SignalKind::interrupt())?,
+                terminate: signal(SignalKind::terminate())?,
+                stop: signal(SignalKind::from_raw(libc::SIGTSTP))?,
+                resume: signal(SignalKind::from_raw(libc::SIGCONT))?,
+            })
+        }
+        #[cfg(not(unix))]
+        Ok(Self {})
+    }
+
+    pub async fn recv(&mut self) -> Signal {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.interrupt.recv() => Signal::Quit,
+                _ = self.terminate.recv() => Signal::Quit,
+                _ = self.stop.recv() => Signal::Suspend,
+                _ = self.resume.recv() => Signal::Resume,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = tokio::signal::ctrl_c().await;
+            Signal::Quit
+        }
+    }
+}
@@ -0,0 +1,494 @@
+use crate::component::{Node, Tree};
+use crate::debug::DebugPane;
+use crate::dispatcher::Dispatcher;
+use crate::help;
+use crate::input::Input;
+use crate::keymap::Keymap;
+#[cfg(feature = "scripting")]
+use crate::macros::Macros;
+// マクロが無効な構成では常にNone
+#[cfg(not(feature = "scripting"))]
+type Macros = ();
+use crate::mouse::Hits;
+use crate::palette::{Outcome, Palette};
+use crate::prompt::{Parse, Prompt};
+use crate::source::InputSource;
+use crate::terminal::{self, Signal, Signals};
+use crossterm::event::{Event, MouseEventKind};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    Frame, Terminal,
+};
+use std::io;
+use tokio::sync::watch;
+use tokio::time::{Duration, Instant, Interval, MissedTickBehavior};
+
+// 状態を指定された領域に描画する関数（フォーカスを受け取らない部品として使える）
+// マウスで操作できる領域はHitsに記録する
+pub type Render<T, A> = Box<dyn Fn(&mut Frame, Rect, &T, &mut Hits<A>)>;
+
+// 1秒あたりの描画回数の上限の既定値
+pub const DEFAULT_MAX_FPS: u32 = 30;
+
+// View: ユーザーインターフェースを描画し、ユーザー操作に反応
+// Tは描画する状態、AはDispatcherへ送るAction
+pub struct View<B: Backend, T, A> {
+    terminal: Terminal<B>,
+    input: Box<dyn InputSource>,
+    dispatcher: Dispatcher<A>,
+    state: watch::Receiver<T>,
+    // 画面を構成する部品
+    tree: Tree<T, A>,
+    // 直前の描画で記録したマウスの当たり判定
+    hits: Hits<A>,
+    keymap: Keymap<A>,
+    // 重ねて表示しているキー割り当ての一覧
+    help: Option<help::Overlay>,
+    // 開いているコマンドパレット
+    palette: Option<Palette>,
+    // 値や式を入力する欄（入力を解釈する関数が設定されたときだけ）
+    prompt: Option<Prompt<A>>,
+    debug: Option<DebugPane<T>>,
+    // 実端末で動かすときだけ設定する
+    signals: Option<Signals>,
+    macros: Option<Macros>,
+    // 描画の最短間隔（ゼロなら上限なし）
+    frame: Duration,
+    // アニメーションのための定期的な再描画
+    animation: Option<Interval>,
+}
+
+impl<B: Backend, T: Clone, A: Clone + Send + 'static> View<B, T, A> {
+    pub fn new(
+        terminal: Terminal<B>,
+        input: Box<dyn InputSource>,
+        dispatcher: Dispatcher<A>,
+        state: watch::Receiver<T>,
+        root: impl Into<Node<T, A>>,
+        keymap: Keymap<A>,
+    ) -> Self {
+        Self {
+            terminal,
+            input,
+            dispatcher,
+            state,
+            tree: Tree::new(root),
+            hits: Hits::new(),
+            keymap,
+            help: None,
+            palette: None,
+            prompt: None,
+            debug: None,
+            signals: None,
+            macros: None,
+            frame: frame_interval(DEFAULT_MAX_FPS),
+            animation: None,
+        }
+    }
+
+    // シグナルに反応する（終了、一時停止、再開時の再描画）
+    pub fn with_signals(mut self, signals: Signals) -> Self {
+        self.signals = Some(signals);
+        self
+    }
+
+    // デバッグモード: Actionログを横に表示し、過去の時点を選んで描画できる
+    pub fn with_debug(mut self, pane: DebugPane<T>) -> Self {
+        self.debug = Some(pane);
+        self
+    }
+
+    // 値や式を入力できるようにする（parseが入力をActionにする）
+    pub fn with_prompt(mut self, parse: Parse<A>) -> Self {
+        self.prompt = Some(Prompt::new(parse));
+        self
+    }
+
+    // 1秒あたりの描画回数の上限（0なら上限なし）
+    pub fn with_max_fps(mut self, fps: u32) -> Self {
+        self.frame = frame_interval(fps);
+        self
+    }
+
+    // 状態が変わらなくてもperiodごとに再描画する（時間で変わる表示のため）
+    pub fn with_animation(mut self, period: Duration) -> Self {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        self.animation = Some(interval);
+        self
+    }
+
+    // キー入力のマクロを記録・再生できるようにする
+    #[cfg(feature = "scripting")]
+    pub fn with_macros(mut self, macros: Macros) -> Self {
+        self.macros = Some(macros);
+        self
+    }
+
+    pub fn terminal(&self) -> &Terminal<B> {
+        &self.terminal
+    }
+
+    // 現在の状態で画面を描画する
+    pub fn draw(&mut self) -> io::Result<()> {
+        self.keymap.set_pane(self.tree.pane());
+        let live = self.state.borrow_and_update().clone();
+        let tree = &mut self.tree;
+        let hits = &mut self.hits;
+        hits.clear();
+        let debug = &self.debug;
+        let keymap = &self.keymap;
+        let help = &mut self.help;
+        let palette = &self.palette;
+        let prompt = self.prompt.as_ref().filter(|p| p.is_open());
+        #[cfg(feature = "scripting")]
+        let naming = self.macros.as_ref().and_then(|m| m.prompt());
+        #[cfg(not(feature = "scripting"))]
+        let naming: Option<&Prompt<A>> = None;
+        #[cfg(feature = "scripting")]
+        let status = self.macros.as_ref().and_then(|m| m.status());
+        self.terminal.draw(|f| {
+            // 最下行はキー割り当てのステータスバー
+            let [area, bar] =
+                Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(f.area());
+            match debug {
+                Some(pane) => {
+                    let [main, side] = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                        .areas(area);
+                    // 過去の時点を選択中ならその状態で描画する
+                    let state = pane.selected_state().unwrap_or(live);
+                    tree.render(f, main, &state, hits);
+                    pane.render(f, side);
+                }
+                None => tree.render(f, area, &live, hits),
+            }
+            // 入力中はステータスバーの代わりに入力欄を表示する
+            match (naming, prompt) {
+                (Some(naming), _) => naming.render(f, bar),
+                (None, Some(prompt)) => prompt.render(f, bar),
+                (None, None) => help::status_bar(f, bar, keymap),
+            }
+            if let Some(help) = help {
+                help.render(f, area, keymap);
+                // 一覧の下に隠れた部分はクリックできない
+                hits.clear();
+            }
+            if let Some(palette) = palette {
+                palette.render(f, area, keymap);
+                hits.clear();
+            }
+            // マクロの記録・再生中は右上に表示する
+            #[cfg(feature = "scripting")]
+            if let Some(status) = status {
+                use ratatui::{layout::Alignment, widgets::Paragraph};
+                let area = Rect {
+                    height: 1,
+                    ..f.area()
+                };
+                f.render_widget(Paragraph::new(status).alignment(Alignment::Right), area);
+            }
+        })?;
+        Ok(())
+    }
+
+    // 入力イベントを処理する。終了要求ならtrue
+    // liveは人が入力したイベントか（マクロの再生ではない）
+    fn handle_event(&mut self, event: &Event, live: bool) -> io::Result<bool> {
+        #[cfg(feature = "scripting")]
+        if let (Some(macros), true) = (&mut self.macros, live) {
+            macros.record(event);
+        }
+        #[cfg(not(feature = "scripting"))]
+        let _ = live;
+        // マウスは描画した領域で判定し、キーマップは通さない
+        // 押した位置の部品にはフォーカスも移す
+        if let Event::Mouse(mouse) = event {
+            if let MouseEventKind::Down(_) = mouse.kind {
+                self.tree.focus_at(Position::new(mouse.column, mouse.row));
+            }
+            if let Some(action) = self.hits.hit(mouse) {
+                self.handle_input(Input::Action(action))?;
+            }
+            return Ok(false);
+        }
+        // 入力欄やパレットを開いている間はキー入力をすべてそちらが受け取る
+        #[cfg(feature = "scripting")]
+        if let (Some(macros), Event::Key(key)) = (&mut self.macros, event) {
+            if macros.prompt().is_some() {
+                macros.handle_key(key);
+                return Ok(false);
+            }
+        }
+        if let (Some(prompt), Event::Key(key)) = (&mut self.prompt, event) {
+            if prompt.is_open() {
+                if let Some(action) = prompt.handle(key) {
+                    self.handle_input(Input::Action(action))?;
+                }
+                return Ok(false);
+            }
+        }
+        // 一覧を開いている間はスクロールのキーを一覧が受け取る
+        if let (Some(help), None, Event::Key(key)) = (&mut self.help, &self.palette, event) {
+            if help.handle(key) {
+                return Ok(false);
+            }
+        }
+        // フォーカスのある部品が先にキー入力を受け取る
+        // 回数や複数キーの入力途中ならキーマップに任せる
+        // キーマップではフォーカスのある部品ごとの割り当てが優先される
+        self.keymap.set_pane(self.tree.pane());
+        if let (None, Event::Key(key)) = (&self.palette, event) {
+            if self.keymap.pending().is_empty() && self.keymap.count().is_none() {
+                let action = self.tree.handle_key(key, &self.state.borrow());
+                if let Some(action) = action {
+                    self.handle_input(Input::Action(action))?;
+                    return Ok(false);
+                }
+            }
+        }
+        let input = match (&mut self.palette, event) {
+            (Some(palette), Event::Key(key)) => match palette.handle(key, &self.keymap) {
+                Outcome::Open => None,
+                Outcome::Closed => {
+                    self.palette = None;
+                    None
+                }
+                Outcome::Run(input) => {
+                    self.palette = None;
+                    Some(input)
+                }
+            },
+            _ => self.keymap.handle(event),
+        };
+        // "10k"のような回数はActionにだけ適用する
+        let count = match input {
+            Some(_) => self.keymap.take_count(),
+            None => None,
+        };
+        match (input, count) {
+            (Some(Input::Quit), _) => Ok(true),
+            (Some(Input::Action(action)), Some(count)) => {
+                for action in self.keymap.repeat(&action, count) {
+                    self.handle_input(Input::Action(action))?;
+                }
+                Ok(false)
+            }
+            (Some(input), _) => self.handle_input(input).map(|()| false),
+            (None, _) => Ok(false),
+        }
+    }
+
+    fn handle_input(&mut self, input: Input<A>) -> io::Result<()> {
+        match input {
+            Input::Action(action) => {
+                // 過去の時点を表示している間は操作を受け付けない
+                if !self.debug.as_ref().is_some_and(|pane| pane.is_paused()) {
+                    self.dispatcher.dispatch(action);
+                }
+            }
+            Input::TimeTravel(travel) => {
+                if let Some(pane) = &mut self.debug {
+                    pane.travel(travel);
+                }
+            }
+            #[cfg(feature = "scripting")]
+            Input::MacroRecord => {
+                let matched = self.keymap.matched();
+                if let Some(macros) = self.macros.as_mut().filter(|m| !m.is_playing()) {
+                    macros.toggle_recording(matched);
+                }
+            }
+            #[cfg(feature = "scripting")]
+            Input::MacroPlay => {
+                // 再生中・記録中に再生するとマクロが自分自身を呼び出してしまう
+                if let Some(macros) = self
+                    .macros
+                    .as_mut()
+                    .filter(|m| !m.is_playing() && !m.is_recording())
+                {
+                    macros.play_last();
+                }
+            }
+            #[cfg(feature = "scripting")]
+            Input::MacroPlayNamed => {
+                if let Some(macros) = self
+                    .macros
+                    .as_mut()
+                    .filter(|m| !m.is_playing() && !m.is_recording())
+                {
+                    macros.prompt_play();
+                }
+            }
+            #[cfg(not(feature = "scripting"))]
+            Input::MacroRecord | Input::MacroPlay | Input::MacroPlayNamed => {}
+            Input::Help => {
+                self.help = match self.help {
+                    Some(_) => None,
+                    None => Some(help::Overlay::new()),
+                }
+            }
+            Input::FocusNext => self.tree.focus_next(),
+            Input::FocusPrev => self.tree.focus_prev(),
+            Input::Palette => self.palette = Some(Palette::new()),
+            Input::Prompt => {
+                if let Some(prompt) = &mut self.prompt {
+                    prompt.open();
+                }
+            }
+            Input::Suspend => self.suspend()?,
+            Input::Quit => {}
+        }
+        Ok(())
+    }
+
+    fn is_playing(&self) -> bool {
+        #[cfg(feature = "scripting")]
+        return self.macros.as_ref().is_some_and(|m| m.is_playing());
+        #[cfg(not(feature = "scripting"))]
+        false
+    }
+
+    fn suspend(&mut self) -> io::Result<()> {
+        // テスト用のBackendなどではプロセスを止めない
+        if self.signals.is_none() {
+            return Ok(());
+        }
+        terminal::suspend()?;
+        // 再開後は画面全体を描き直す
+        self.terminal.clear()
+    }
+
+    // 入力が終わるか終了要求があるまで動かす
+    // 入力は単一の非同期ストリームから受け取る（event::pollでランタイムを止めない）
+    // 描画は状態の変更・入力・リサイズ・アニメーションがあったときだけ、上限のfpsまでにまとめる
+    pub async fn run(&mut self) -> io::Result<()> {
+        // 入力が終わった後もマクロの再生が終わるまでは動かす
+        let mut ended = false;
+        let mut dirty = true;
+        let mut next_frame = Instant::now();
+
+        loop {
+            if dirty && Instant::now() >= next_frame {
+                self.draw()?;
+                dirty = false;
+                next_frame = Instant::now() + self.frame;
+            }
+            if ended && !self.is_playing() {
+                break;
+            }
+
+            tokio::select! {
+                maybe_event = self.input.next_event(), if !ended => match maybe_event {
+                    Some(Ok(event)) => {
+                        // マウスは最新の配置で当たり判定する
+                        if let (Event::Mouse(_), true) = (&event, dirty) {
+                            self.draw()?;
+                            dirty = false;
+                            next_frame = Instant::now() + self.frame;
+                        }
+                        dirty |= affects_screen(&event);
+                        // リサイズはフレームの間引きを待たずにすぐ配置し直す
+                        if let Event::Resize(..) = event {
+                            next_frame = Instant::now();
+                        }
+                        if self.handle_event(&event, true)? {
+                            break;
+                        }
+                        if !self.input.is_interactive() {
+                            self.dispatcher.flush().await;
+                        }
+                    }
+                    Some(Err(e)) => return Err(e),
+                    // 入力が終わったら、適用待ちのActionを反映して終了
+                    None => {
+                        self.dispatcher.flush().await;
+                        ended = true;
+                    }
+                },
+                // Storeの状態が変わったら再描画
+                changed = self.state.changed() => {
+                    if changed.is_err() {
+                        return Err(io::Error::other("dispatcher stopped"));
+                    }
+                    dirty = true;
+                }
+                maybe_event = next_macro_event(&mut self.macros) => {
+                    // 再生が終わったときは表示を消すために再描画するだけ
+                    dirty = true;
+                    if let Some(event) = maybe_event {
+                        if self.handle_event(&event?, false)? {
+                            break;
+                        }
+                        if ended {
+                            self.dispatcher.flush().await;
+                        }
+                    }
+                }
+                signal = next_signal(&mut self.signals) => {
+                    match signal {
+                        Signal::Quit => break,
+                        Signal::Suspend => self.suspend()?,
+                        Signal::Resume => self.terminal.clear()?,
+                    }
+                    dirty = true;
+                }
+                _ = next_tick(&mut self.animation) => dirty = true,
+                // 描画を間引いている間に溜まった変更を次のフレームで描く
+                _ = tokio::time::sleep_until(next_frame), if dirty => {}
+            }
+        }
+        // 終了要求の前にdispatchしたActionを反映してから返す（呼び出し側が状態を保存するため）
+        self.dispatcher.flush().await;
+        self.terminal.clear()?;
+        Ok(())
+    }
+}
+
+fn frame_interval(fps: u32) -> Duration {
+    match fps {
+        0 => Duration::ZERO,
+        fps => Duration::from_secs(1) / fps,
+    }
+}
+
+// 画面の見た目が変わり得る入力か（マウスの移動などでは描き直さない）
+// マウスのボタンを押すとフォーカスが移ることがある
+fn affects_screen(event: &Event) -> bool {
+    match event {
+        Event::Key(_) | Event::Paste(_) | Event::Resize(..) => true,
+        Event::Mouse(mouse) => matches!(mouse.kind, MouseEventKind::Down(_)),
+        _ => false,
+    }
+}
+
+async fn next_tick(animation: &mut Option<Interval>) {
+    match animation {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+async fn next_signal(signals: &mut Option<Signals>) -> Signal {
+    match signals {
+        Some(signals) => signals.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(feature = "scripting")]
+async fn next_macro_event(macros: &mut Option<Macros>) -> Option<io::Result<Event>> {
+    match macros {
+        Some(macros) => macros.next_event().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+async fn next_macro_event(_: &mut Option<Macros>) -> Option<io::Result<Event>> {
+    std::future::pending().await
+}
@@ -0,0 +1,85 @@
+use testtui::bigint::BigInt;
+
+fn big(text: &str) -> BigInt {
+    text.parse().unwrap()
+}
+
+// 符号の違う加算で、基数10^9の桁をまたいで借りる
+#[test]
+fn mixed_sign_add_borrows_across_digits() {
+    assert_eq!(big("1000000000").add(&big("-1")), big("999999999"));
+    assert_eq!(big("-1000000000").add(&big("1")), big("-999999999"));
+    assert_eq!(
+        big("1000000000000000000").add(&big("-1")),
+        big("999999999999999999")
+    );
+    // 絶対値の大きい方の符号になる
+    assert_eq!(big("1").add(&big("-1000000000")), big("-999999999"));
+    assert_eq!(big("-5").add(&big("5")), BigInt::default());
+    assert_eq!(big("-5").add(&big("5")).to_string(), "0");
+}
+
+#[test]
+fn same_sign_add_carries_into_a_new_digit() {
+    assert_eq!(big("999999999").add(&big("1")), big("1000000000"));
+    assert_eq!(big("-999999999").add(&big("-1")), big("-1000000000"));
+}
+
+#[test]
+fn mul_carries_between_digits() {
+    assert_eq!(
+        big("999999999").mul(&big("999999999")),
+        big("999999998000000001")
+    );
+    assert_eq!(
+        big("999999999999999999").mul(&big("-999999999999999999")),
+        big("-999999999999999998000000000000000001")
+    );
+    assert!(big("123456789123").mul(&BigInt::default()).is_zero());
+    assert_eq!(big("-3").mul(&big("-4")), big("12"));
+}
+
+// i64の両端はちょうど収まり、その外は収まらない
+#[test]
+fn to_i64_at_the_i64_bounds() {
+    assert_eq!(BigInt::from(i64::MAX).to_i64(), Some(i64::MAX));
+    assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+    assert_eq!(big("9223372036854775807").to_i64(), Some(i64::MAX));
+    assert_eq!(big("-9223372036854775808").to_i64(), Some(i64::MIN));
+    assert_eq!(big("9223372036854775808").to_i64(), None);
+    assert_eq!(big("-9223372036854775809").to_i64(), None);
+    assert_eq!(BigInt::from(i64::MIN).to_string(), "-9223372036854775808");
+}
+
+#[test]
+fn display_and_from_str_round_trip() {
+    for text in [
+        "0",
+        "7",
+        "-7",
+        "1000000000",
+        "-1000000001",
+        "100000000000000000000000000",
+        "-123456789000000001",
+    ] {
+        assert_eq!(big(text).to_string(), text);
+    }
+    // 先頭の0や"+"、"-0"は正規の形で表示する
+    assert_eq!(big("+000000000012").to_string(), "12");
+    assert_eq!(big("-0").to_string(), "0");
+    assert_eq!(big("-0"), BigInt::default());
+}
+
+#[test]
+fn malformed_integers_are_rejected() {
+    for text in ["", "-", "+", "1_000", "12a", " 1", "--1"] {
+        assert!(text.parse::<BigInt>().is_err(), "{text:?}");
+    }
+}
+
+#[test]
+fn ordering_follows_the_sign() {
+    assert!(big("-1000000000") < big("-999999999"));
+    assert!(big("-1") < BigInt::default());
+    assert!(big("999999999") < big("1000000000"));
+}
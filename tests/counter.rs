@@ -0,0 +1,109 @@
+use testtui::bigint::BigInt;
+use testtui::counter::{
+    parse, Counter, CounterAction, CounterState, CounterStore, Overflow, Value,
+};
+use testtui::Store;
+
+#[test]
+fn prompt_parses_signed_values() {
+    assert!(matches!(parse("+5"), Ok(CounterAction::Add(5))));
+    assert!(matches!(parse("- 3"), Ok(CounterAction::Add(-3))));
+    assert!(matches!(parse("=-5"), Ok(CounterAction::Set(-5))));
+    assert!(matches!(parse("*2"), Ok(CounterAction::Multiply(2))));
+    assert!(parse("+").is_err());
+}
+
+// i32の範囲の端はそのまま受け付け、外は拒否する
+#[test]
+fn prompt_accepts_the_i32_range_ends() {
+    assert!(matches!(
+        parse("-2147483648"),
+        Ok(CounterAction::Add(i32::MIN))
+    ));
+    assert!(matches!(
+        parse("=-2147483648"),
+        Ok(CounterAction::Set(i32::MIN))
+    ));
+    assert!(matches!(
+        parse("+2147483647"),
+        Ok(CounterAction::Add(i32::MAX))
+    ));
+    assert!(parse("-2147483649").is_err());
+    assert!(parse("2147483648").is_err());
+}
+
+fn counter(count: Value, overflow: Overflow) -> CounterStore {
+    let mut counter = Counter::new("x".to_owned());
+    counter.count = count;
+    counter.overflow = overflow;
+    CounterStore::with_state(CounterState {
+        counters: vec![counter],
+        selected: 0,
+    })
+}
+
+fn count(store: &CounterStore) -> &Value {
+    &store.state().current().count
+}
+
+// i64に収まらない値が残っていてもWrapは端で止まる（パニックしない）
+#[test]
+fn wrap_stops_at_the_bound_for_big_values() {
+    let big: BigInt = "99999999999999999999".parse().unwrap();
+    let mut store = counter(Value::Big(big), Overflow::Wrap);
+    store.reduce(&CounterAction::Increment);
+    assert_eq!(*count(&store), Value::Int(i32::MAX));
+
+    let negative: BigInt = "-99999999999999999999".parse().unwrap();
+    let mut store = counter(Value::Big(negative), Overflow::Wrap);
+    store.reduce(&CounterAction::Decrement);
+    assert_eq!(*count(&store), Value::Int(i32::MIN));
+}
+
+#[cfg(feature = "persistence")]
+fn load(name: &str, json: &str) -> CounterState {
+    let path = std::env::temp_dir().join(format!("testtui-state-{name}-{}", std::process::id()));
+    std::fs::write(&path, json).unwrap();
+    let state = testtui::persist::load(&path).unwrap().unwrap();
+    let _ = std::fs::remove_file(&path);
+    state
+}
+
+// 範囲外の値は読み込み時に範囲へ収めるので、その後の演算で回り込める
+#[cfg(feature = "persistence")]
+#[test]
+fn loaded_values_are_clamped_to_their_range() {
+    let state = load(
+        "clamp",
+        r#"{"counters":[{"name":"x","count":"99999999999999999999","overflow":"wrap"}],"selected":0}"#,
+    );
+    assert_eq!(state.current().count, Value::Int(i32::MAX));
+    let mut store = CounterStore::with_state(state);
+    store.reduce(&CounterAction::Increment);
+    assert_eq!(*count(&store), Value::Int(i32::MIN));
+}
+
+// 上下限が逆のファイルは入れ替えて読む（clampでパニックしない）
+#[cfg(feature = "persistence")]
+#[test]
+fn loaded_bounds_in_the_wrong_order_are_swapped() {
+    let state = load(
+        "bounds",
+        r#"{"counters":[{"name":"x","count":50,"min":10,"max":0}],"selected":0}"#,
+    );
+    let counter = state.current();
+    assert_eq!((counter.min, counter.max), (Some(0), Some(10)));
+    assert_eq!(counter.count, Value::Int(10));
+}
+
+// 文字列で書かれた小さな値は符号ではなく値そのもので範囲に収める
+#[cfg(feature = "persistence")]
+#[test]
+fn loaded_big_values_that_fit_become_ints() {
+    let state = load(
+        "fits",
+        r#"{"counters":[{"name":"x","count":"5"},{"name":"y","count":"-7","min":-3}],"selected":0}"#,
+    );
+    assert_eq!(state.counters[0].count, Value::Int(5));
+    assert_eq!(state.counters[1].count, Value::Int(-3));
+}
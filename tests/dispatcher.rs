@@ -0,0 +1,288 @@
+use std::sync::{Arc, Mutex};
+use testtui::{Context, DispatchError, Dispatcher, DispatcherBuilder, Middleware, Store, StoreId};
+
+// Actionを受けた順にStoreの番号を記録する
+type Log = Arc<Mutex<Vec<usize>>>;
+
+// Probe: 受け取ったActionを記録し、その合計を状態にするStore（Nで型を分ける）
+struct Probe<const N: usize> {
+    log: Log,
+    total: u32,
+}
+
+impl<const N: usize> Store for Probe<N> {
+    type State = u32;
+    type Action = u32;
+
+    fn state(&self) -> &u32 {
+        &self.total
+    }
+
+    fn reduce(&mut self, action: &u32) {
+        self.log.lock().unwrap().push(N);
+        self.total += action;
+    }
+}
+
+fn probe<const N: usize>(builder: &mut DispatcherBuilder<u32>, log: &Log) -> StoreId<Probe<N>> {
+    let (id, _) = builder.register(Probe::<N> {
+        log: log.clone(),
+        total: 0,
+    });
+    id
+}
+
+// actionsを順に流し、Actionごとの更新順を返す
+async fn run(builder: DispatcherBuilder<u32>, log: &Log, actions: &[u32]) -> Vec<Vec<usize>> {
+    let dispatcher = builder.spawn();
+    let mut orders = Vec::new();
+    for &action in actions {
+        dispatcher.dispatch(action);
+        dispatcher.flush().await;
+        orders.push(std::mem::take(&mut *log.lock().unwrap()));
+    }
+    orders
+}
+
+// 依存のないStoreは登録順に更新される
+#[tokio::test]
+async fn stores_without_dependencies_keep_registration_order() {
+    let log = Log::default();
+    let mut builder = Dispatcher::builder();
+    probe::<0>(&mut builder, &log);
+    probe::<1>(&mut builder, &log);
+    probe::<2>(&mut builder, &log);
+    assert_eq!(run(builder, &log, &[1]).await, [vec![0, 1, 2]]);
+}
+
+// wait_forした先のStoreが先に更新される（連鎖もたどる）
+#[tokio::test]
+async fn wait_for_orders_dependencies_first() {
+    let log = Log::default();
+    let mut builder = Dispatcher::builder();
+    let a = probe::<0>(&mut builder, &log);
+    let b = probe::<1>(&mut builder, &log);
+    let c = probe::<2>(&mut builder, &log);
+    builder.wait_for(a, b).unwrap();
+    builder.wait_for(b, c).unwrap();
+    assert_eq!(run(builder, &log, &[1]).await, [vec![2, 1, 0]]);
+}
+
+// 条件付きの依存は一致しないActionでは順序に影響しない
+#[tokio::test]
+async fn conditional_dependency_applies_only_to_matching_actions() {
+    let log = Log::default();
+    let mut builder = Dispatcher::builder();
+    let a = probe::<0>(&mut builder, &log);
+    let b = probe::<1>(&mut builder, &log);
+    builder.wait_for_if(a, b, |action| action % 2 == 0).unwrap();
+    let orders = run(builder, &log, &[1, 2, 3]).await;
+    assert_eq!(orders, [vec![0, 1], vec![1, 0], vec![0, 1]]);
+}
+
+fn chain(error: DispatchError) -> Vec<String> {
+    let DispatchError::Cycle(chain) = error;
+    // "dispatcher::Probe<0>"のような型名から末尾だけを残す
+    chain
+        .iter()
+        .map(|name| name.rsplit("::").next().unwrap().to_owned())
+        .collect()
+}
+
+#[tokio::test]
+async fn self_dependency_is_a_cycle() {
+    let log = Log::default();
+    let mut builder = Dispatcher::builder();
+    let a = probe::<0>(&mut builder, &log);
+    let error = builder.wait_for(a, a).unwrap_err();
+    assert_eq!(chain(error), ["Probe<0>", "Probe<0>"]);
+}
+
+// 循環を作る依存は登録時に拒否され、連鎖を順に報告する
+#[tokio::test]
+async fn three_store_cycle_is_rejected_with_its_chain() {
+    let log = Log::default();
+    let mut builder = Dispatcher::builder();
+    let a = probe::<0>(&mut builder, &log);
+    let b = probe::<1>(&mut builder, &log);
+    let c = probe::<2>(&mut builder, &log);
+    builder.wait_for(a, b).unwrap();
+    builder.wait_for(b, c).unwrap();
+    // 条件付きでも、いずれかのActionで循環し得るなら拒否する
+    let error = builder.wait_for_if(c, a, |_| false).unwrap_err();
+    assert_eq!(
+        chain(error),
+        ["Probe<2>", "Probe<1>", "Probe<0>", "Probe<2>"]
+    );
+
+    // 拒否した依存は登録されず、元の順序のまま動く
+    assert_eq!(run(builder, &log, &[1]).await, [vec![2, 1, 0]]);
+}
+
+// Middlewareの呼び出しを"名前 段階 Action"の形で記録する
+type Trace = Arc<Mutex<Vec<String>>>;
+
+// Tap: 呼び出しを記録し、beforeでrewriteに従ってActionを書き換える（Noneなら中断）
+struct Tap {
+    name: &'static str,
+    trace: Trace,
+    rewrite: fn(u32) -> Option<u32>,
+}
+
+impl Middleware<u32> for Tap {
+    fn before(&mut self, action: u32, _: &mut Context<'_, u32>) -> Option<u32> {
+        let mut trace = self.trace.lock().unwrap();
+        trace.push(format!("{} before {action}", self.name));
+        (self.rewrite)(action)
+    }
+
+    fn after(&mut self, action: &u32, _: &mut Context<'_, u32>) {
+        let mut trace = self.trace.lock().unwrap();
+        trace.push(format!("{} after {action}", self.name));
+    }
+}
+
+fn tap(name: &'static str, trace: &Trace, rewrite: fn(u32) -> Option<u32>) -> Tap {
+    Tap {
+        name,
+        trace: trace.clone(),
+        rewrite,
+    }
+}
+
+// beforeは登録順、afterは逆順に呼ばれ、書き換えたActionが後のMiddlewareとStoreに届く
+#[tokio::test]
+async fn middleware_rewrites_actions_in_registration_order() {
+    let log = Log::default();
+    let trace = Trace::default();
+    let mut builder = Dispatcher::builder();
+    let (_, state) = builder.register(Probe::<0> {
+        log: log.clone(),
+        total: 0,
+    });
+    builder.middleware(tap("outer", &trace, |n| Some(n * 2)));
+    builder.middleware(tap("inner", &trace, |n| Some(n + 1)));
+    run(builder, &log, &[3]).await;
+
+    assert_eq!(*state.borrow(), 7);
+    assert_eq!(
+        *trace.lock().unwrap(),
+        [
+            "outer before 3",
+            "inner before 6",
+            "inner after 7",
+            "outer after 7"
+        ]
+    );
+}
+
+// Noneで中断したActionはStoreにも、後のMiddlewareにも届かない
+#[tokio::test]
+async fn middleware_can_stop_an_action() {
+    let log = Log::default();
+    let trace = Trace::default();
+    let mut builder = Dispatcher::builder();
+    probe::<0>(&mut builder, &log);
+    builder.middleware(tap("filter", &trace, |n| (n % 2 == 0).then_some(n)));
+    builder.middleware(tap("inner", &trace, Some));
+    let orders = run(builder, &log, &[1, 2]).await;
+
+    assert_eq!(orders, [vec![], vec![0]]);
+    assert_eq!(
+        *trace.lock().unwrap(),
+        [
+            "filter before 1",
+            "filter before 2",
+            "inner before 2",
+            "inner after 2",
+            "filter after 2"
+        ]
+    );
+}
+
+// (段階, previous, state)の記録
+type Seen = Arc<Mutex<Vec<(&'static str, u32, u32)>>>;
+
+// Watch: beforeとafterで見えるStoreの状態を記録する
+struct Watch {
+    id: StoreId<Probe<0>>,
+    seen: Seen,
+}
+
+impl Middleware<u32> for Watch {
+    fn before(&mut self, action: u32, ctx: &mut Context<'_, u32>) -> Option<u32> {
+        let seen = ("before", ctx.previous(self.id), *ctx.state(self.id));
+        self.seen.lock().unwrap().push(seen);
+        Some(action)
+    }
+
+    fn after(&mut self, _: &u32, ctx: &mut Context<'_, u32>) {
+        let seen = ("after", ctx.previous(self.id), *ctx.state(self.id));
+        self.seen.lock().unwrap().push(seen);
+    }
+}
+
+// previousは処理中のActionの適用前、stateはafterでは適用後の状態
+#[tokio::test]
+async fn context_shows_the_previous_and_current_state() {
+    let log = Log::default();
+    let mut builder = Dispatcher::builder();
+    let id = probe::<0>(&mut builder, &log);
+    let seen = Seen::default();
+    builder.middleware(Watch {
+        id,
+        seen: Arc::clone(&seen),
+    });
+    run(builder, &log, &[3, 2]).await;
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            ("before", 0, 0),
+            ("after", 0, 3),
+            ("before", 3, 3),
+            ("after", 3, 5)
+        ]
+    );
+}
+
+// FollowUp: afterで、受け取ったActionの10倍を後続Actionとして1回だけ発行する
+struct FollowUp {
+    trace: Trace,
+}
+
+impl Middleware<u32> for FollowUp {
+    fn before(&mut self, action: u32, _: &mut Context<'_, u32>) -> Option<u32> {
+        self.trace.lock().unwrap().push(action.to_string());
+        Some(action)
+    }
+
+    fn after(&mut self, action: &u32, ctx: &mut Context<'_, u32>) {
+        if *action < 10 {
+            ctx.dispatch(action * 10);
+        }
+    }
+}
+
+// 後続Actionは、すでにキューにある次のActionより先に処理される
+#[tokio::test]
+async fn follow_up_actions_run_before_the_next_queued_action() {
+    let log = Log::default();
+    let trace = Trace::default();
+    let mut builder = Dispatcher::builder();
+    let (_, state) = builder.register(Probe::<0> {
+        log: log.clone(),
+        total: 0,
+    });
+    builder.middleware(FollowUp {
+        trace: trace.clone(),
+    });
+    let dispatcher = builder.spawn();
+    // flushを挟まずに積み、キューに並んだ状態にする
+    dispatcher.dispatch(1);
+    dispatcher.dispatch(2);
+    dispatcher.flush().await;
+
+    assert_eq!(*trace.lock().unwrap(), ["1", "10", "2", "20"]);
+    assert_eq!(*state.borrow(), 33);
+}
@@ -0,0 +1,41 @@
+use testtui::counter::{CounterAction, CounterStore, Overflow, Value};
+use testtui::{History, Store};
+
+// 状態が変わらないActionは履歴に残らない
+#[test]
+fn unchanged_state_is_not_recorded() {
+    let mut history = History::new(CounterStore::new(), 100);
+    history.reduce(&CounterAction::SetMax(Some(0)));
+    // 上限で止まる増加と、1つしかないカウンターの選択
+    for _ in 0..3 {
+        history.reduce(&CounterAction::Increment);
+    }
+    history.reduce(&CounterAction::SelectNext);
+    assert_eq!(history.state().undo, 1);
+
+    // 拒否された演算はエラーを表示するだけで履歴に残らない
+    history.reduce(&CounterAction::SetOverflow(Overflow::Error));
+    history.reduce(&CounterAction::Increment);
+    let timeline = history.state();
+    assert_eq!(timeline.undo, 2);
+    assert!(timeline.current.current().error.is_some());
+
+    // Undoは直前の変更を戻す
+    history.reduce(&CounterAction::Undo);
+    history.reduce(&CounterAction::Undo);
+    let timeline = history.state();
+    assert_eq!((timeline.undo, timeline.redo), (0, 2));
+    assert_eq!(timeline.current.current().max, None);
+    assert_eq!(timeline.current.current().count, Value::Int(0));
+}
+
+// 何も変えないActionはRedoできる履歴を消さない
+#[test]
+fn unchanged_state_keeps_the_redo_history() {
+    let mut history = History::new(CounterStore::new(), 100);
+    history.reduce(&CounterAction::Increment);
+    history.reduce(&CounterAction::Undo);
+    history.reduce(&CounterAction::SelectPrev);
+    history.reduce(&CounterAction::Redo);
+    assert_eq!(history.state().current.current().count, Value::Int(1));
+}
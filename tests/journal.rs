@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::PathBuf;
+use testtui::counter::{CounterAction, CounterState, CounterStore, Value};
+use testtui::journal::{self, Journal, JournalError};
+use testtui::{Dispatcher, History, Store};
+
+// テストごとに空の一時ディレクトリにジャーナルを置く
+fn journal_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("testtui-journal-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir.join("journal.jsonl")
+}
+
+fn write(path: &PathBuf, text: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, text).unwrap();
+}
+
+const STATE: &str = r#"{"State":{"counters":[{"name":"a","count":5}],"selected":0}}"#;
+
+fn count(store: &CounterStore) -> Value {
+    store.state().current().count.clone()
+}
+
+#[test]
+fn missing_journal_is_not_an_error() {
+    let path = journal_path("missing");
+    let mut store = CounterStore::new();
+    assert!(journal::replay(&path, &mut store).unwrap().is_none());
+}
+
+// 書き込み途中で途切れた最終行は無視し、それまでの記録を適用する
+#[test]
+fn torn_last_record_is_ignored() {
+    let path = journal_path("torn");
+    write(
+        &path,
+        &format!("{STATE}\n{{\"Action\":\"Increment\"}}\n{{\"Action\":\"Incr"),
+    );
+    let mut store = CounterStore::new();
+    let replay = journal::replay(&path, &mut store).unwrap().unwrap();
+    assert_eq!((replay.records, replay.torn), (2, true));
+    assert_eq!(count(&store), Value::Int(6));
+}
+
+// 改行で終わっていない最終行は、読めても書き込み途中とみなす
+#[test]
+fn last_record_without_newline_is_torn() {
+    let path = journal_path("newline");
+    write(&path, &format!("{STATE}\n{{\"Action\":\"Increment\"}}"));
+    let mut store = CounterStore::new();
+    let replay = journal::replay(&path, &mut store).unwrap().unwrap();
+    assert_eq!((replay.records, replay.torn), (1, true));
+    assert_eq!(count(&store), Value::Int(5));
+}
+
+// 最終行以外が壊れていれば行番号付きのエラーにする
+#[test]
+fn corrupt_record_in_the_middle_is_an_error() {
+    let path = journal_path("corrupt");
+    write(
+        &path,
+        &format!("{STATE}\n{{\"Action\":\n{{\"Action\":\"Increment\"}}\n"),
+    );
+    let mut store = CounterStore::new();
+    match journal::replay(&path, &mut store) {
+        Err(JournalError::Corrupt { line, .. }) => assert_eq!(line, 2),
+        _ => panic!("expected a corrupt record"),
+    }
+}
+
+// Journalを付けたDispatcherにactionsを流し、ジャーナルの各行を返す
+async fn record(path: &PathBuf, actions: &[CounterAction], compact_every: usize) -> Vec<String> {
+    let mut builder = Dispatcher::builder();
+    let store = CounterStore::new();
+    let initial = store.state().clone();
+    let (id, _) = builder.register(History::new(store, 100));
+    let journal = Journal::open(
+        path.clone(),
+        id,
+        &initial,
+        |t| t.current.clone(),
+        compact_every,
+    );
+    builder.middleware(journal.unwrap());
+    let dispatcher = builder.spawn();
+    for action in actions {
+        dispatcher.dispatch(action.clone());
+    }
+    dispatcher.flush().await;
+    let text = fs::read_to_string(path).unwrap();
+    text.lines().map(str::to_owned).collect()
+}
+
+// compact_everyごとに現在の状態だけのジャーナルに置き換える
+#[tokio::test]
+async fn journal_compacts_after_compact_every_records() {
+    let path = journal_path("compact");
+    let actions = vec![CounterAction::Increment; 4];
+    let lines = record(&path, &actions, 3).await;
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(r#"{"State":"#));
+    assert!(lines[0].contains(r#""count":3"#));
+    assert_eq!(lines[1], r#"{"Action":"Increment"}"#);
+
+    let mut store = CounterStore::new();
+    let replay = journal::replay(&path, &mut store).unwrap().unwrap();
+    assert_eq!((replay.records, replay.torn), (2, false));
+    assert_eq!(count(&store), Value::Int(4));
+}
+
+// Undo/Redoは履歴がなくても再生できるよう結果の状態を記録する
+#[tokio::test]
+async fn undo_and_redo_are_written_as_states() {
+    let path = journal_path("undo");
+    let actions = [
+        CounterAction::Increment,
+        CounterAction::Increment,
+        CounterAction::Undo,
+        CounterAction::Undo,
+        CounterAction::Redo,
+    ];
+    let lines = record(&path, &actions, 100).await;
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[1], r#"{"Action":"Increment"}"#);
+    for line in &lines[3..] {
+        assert!(line.starts_with(r#"{"State":"#), "{line}");
+    }
+    assert!(lines[5].contains(r#""count":1"#));
+
+    let mut store = CounterStore::new();
+    journal::replay(&path, &mut store).unwrap();
+    assert_eq!(count(&store), Value::Int(1));
+}
+
+// 状態ファイルとジャーナルの名前が拡張子だけ違っても一時ファイルは衝突しない
+#[tokio::test]
+async fn state_file_and_journal_use_separate_temp_files() {
+    let path = journal_path("tmp").with_file_name("x.jsonl");
+    let state_file = path.with_extension("json");
+    // 拡張子だけを替えた一時ファイル名が使えないようにしておく
+    fs::create_dir_all(path.with_extension("tmp")).unwrap();
+
+    let store = CounterStore::new();
+    testtui::persist::save(&state_file, store.state()).unwrap();
+    let lines = record(&path, &[CounterAction::Increment], 100).await;
+    assert_eq!(lines.len(), 2);
+    assert!(testtui::persist::load::<CounterState>(&state_file)
+        .unwrap()
+        .is_some());
+}
+
+// 壊れたジャーナルは作り直す前に.corruptを付けた名前で残す
+#[test]
+fn corrupt_journal_is_set_aside() {
+    let path = journal_path("aside");
+    let text = format!("{STATE}\n{{\"Action\":\n{{\"Action\":\"Increment\"}}\n");
+    write(&path, &text);
+    let aside = journal::set_aside(&path).unwrap();
+    assert_eq!(aside, path.with_file_name("journal.jsonl.corrupt"));
+    assert_eq!(fs::read_to_string(&aside).unwrap(), text);
+    assert!(!path.exists());
+}
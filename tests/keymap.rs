@@ -0,0 +1,182 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use testtui::{Input, KeyChord, Keymap, KeymapError, NamedAction};
+
+// キーマップだけを試すためのAction
+#[derive(Clone, Debug, PartialEq)]
+enum Act {
+    A,
+    B,
+}
+
+impl NamedAction for Act {
+    fn named() -> Vec<(&'static str, Self)> {
+        vec![("a", Act::A), ("b", Act::B)]
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Act::A => "A",
+            Act::B => "B",
+        }
+    }
+}
+
+fn keymap(entries: &[(&str, &str)]) -> Keymap<Act> {
+    Keymap::new(entries.iter().copied()).unwrap()
+}
+
+fn press(keymap: &mut Keymap<Act>, code: KeyCode) -> Option<Act> {
+    let event = Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+    match keymap.handle(&event) {
+        Some(Input::Action(action)) => Some(action),
+        _ => None,
+    }
+}
+
+#[test]
+fn chords_parse_modifiers() {
+    let chord = KeyChord::parse("ctrl-alt-x").unwrap();
+    assert_eq!(chord.code, KeyCode::Char('x'));
+    assert_eq!(chord.modifiers, KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+    let chord = KeyChord::parse("Shift-Up").unwrap();
+    assert_eq!(chord.code, KeyCode::Up);
+    assert_eq!(chord.modifiers, KeyModifiers::SHIFT);
+
+    // 文字キーのShiftは文字自体に含まれる（Shift+Aは'A'として届く）
+    let chord = KeyChord::parse("shift-a").unwrap();
+    assert_eq!(chord, KeyChord::new(KeyCode::Char('A'), KeyModifiers::NONE));
+    assert_eq!(
+        chord,
+        KeyChord::new(KeyCode::Char('A'), KeyModifiers::SHIFT)
+    );
+    assert_eq!(chord, KeyChord::parse("A").unwrap());
+    assert_ne!(chord, KeyChord::parse("a").unwrap());
+    assert_eq!(KeyChord::parse("f12").unwrap().code, KeyCode::F(12));
+}
+
+// "shift-a"の割り当ては小文字のaでは動かず、Shift+Aで動く
+#[test]
+fn shift_letter_matches_the_uppercase_key() {
+    let mut keymap = keymap(&[("shift-a", "a")]);
+    assert_eq!(press(&mut keymap, KeyCode::Char('a')), None);
+    let event = Event::Key(KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT));
+    assert!(matches!(keymap.handle(&event), Some(Input::Action(Act::A))));
+}
+
+#[test]
+fn shift_tab_is_backtab() {
+    let chord = KeyChord::parse("shift-tab").unwrap();
+    assert_eq!(chord, KeyChord::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+    assert_eq!(chord, KeyChord::parse("backtab").unwrap());
+    assert_eq!(chord.to_string(), "shift-tab");
+}
+
+#[test]
+fn incomplete_and_unknown_keys_are_rejected() {
+    assert!(KeyChord::parse("ctrl-").is_none());
+    assert!(KeyChord::parse("ctrl-alt-").is_none());
+    assert!(KeyChord::parse("nope").is_none());
+    // "-"キーそのものは書ける
+    assert_eq!(KeyChord::parse("-").unwrap().code, KeyCode::Char('-'));
+}
+
+// 一方が他方の先頭と一致するキー列は押し分けられないので報告する
+#[test]
+fn prefix_conflicts_are_reported() {
+    let Err(errors) = Keymap::<Act>::new([("g", "a"), ("g g", "b"), ("x", "c")]) else {
+        panic!("expected errors");
+    };
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(&errors[0], KeymapError::UnknownCommand { command, .. } if command == "c"));
+    assert!(matches!(
+        &errors[1],
+        KeymapError::Conflict { prefix, keys } if prefix == "g" && keys == "g g"
+    ));
+}
+
+#[test]
+fn later_entries_override_earlier_ones() {
+    let mut keymap = keymap(&[("k", "a"), ("k", "b")]);
+    assert_eq!(press(&mut keymap, KeyCode::Char('k')), Some(Act::B));
+    assert_eq!(keymap.bindings().len(), 1);
+}
+
+// キー列の途中で続かないキーが来たら、そのキー単独で解釈し直す
+#[test]
+fn broken_sequence_falls_back_to_the_single_key() {
+    let mut keymap = keymap(&[("g g", "a"), ("x", "b")]);
+    assert_eq!(press(&mut keymap, KeyCode::Char('g')), None);
+    assert_eq!(keymap.pending().len(), 1);
+    assert_eq!(press(&mut keymap, KeyCode::Char('g')), Some(Act::A));
+
+    assert_eq!(press(&mut keymap, KeyCode::Char('g')), None);
+    assert_eq!(press(&mut keymap, KeyCode::Char('x')), Some(Act::B));
+    assert!(keymap.pending().is_empty());
+}
+
+// "none"でプリセットの割り当てを外し、そのキーを別の割り当てに使える
+#[test]
+fn none_unbinds_a_key() {
+    let mut keymap = keymap(&[("d d", "a"), ("d", "none"), ("d d", "none"), ("d", "b")]);
+    assert_eq!(press(&mut keymap, KeyCode::Char('d')), Some(Act::B));
+    assert_eq!(keymap.bindings().len(), 1);
+}
+
+// 設定ファイルからプリセットの"d d"を外して"d"を割り当てる
+#[test]
+fn config_can_unbind_a_preset_sequence() {
+    const PRESET: testtui::keymap::Preset = &[("d d", "a")];
+    let config = testtui::KeymapConfig {
+        preset: Some("p".to_owned()),
+        keys: [("d", "b"), ("d d", "none")]
+            .into_iter()
+            .map(|(k, c)| (k.to_owned(), c.to_owned()))
+            .collect(),
+    };
+    let mut keymap = Keymap::<Act>::from_config(&[("p", PRESET)], &config).unwrap();
+    assert_eq!(press(&mut keymap, KeyCode::Char('d')), Some(Act::B));
+}
+
+// 部品ごとの割り当ては、その部品にフォーカスがあるときだけ全体の割り当てを上書きする
+#[test]
+fn pane_bindings_override_global_ones_while_focused() {
+    let mut keymap = keymap(&[("k", "a"), ("list:k", "b"), ("list:j", "a")]);
+    assert_eq!(press(&mut keymap, KeyCode::Char('k')), Some(Act::A));
+    assert_eq!(press(&mut keymap, KeyCode::Char('j')), None);
+
+    keymap.set_pane(Some("list"));
+    assert_eq!(press(&mut keymap, KeyCode::Char('k')), Some(Act::B));
+    assert_eq!(press(&mut keymap, KeyCode::Char('j')), Some(Act::A));
+    // 上書きされた全体の割り当ては一覧に出ない
+    let active: Vec<_> = keymap.active().map(|b| b.keys.to_string()).collect();
+    assert_eq!(active, ["k", "j"]);
+
+    keymap.set_pane(Some("detail"));
+    assert_eq!(press(&mut keymap, KeyCode::Char('k')), Some(Act::A));
+}
+
+// ":"キーそのものは部品の名前と区別する。部品の割り当ても"none"で外せる
+#[test]
+fn pane_prefix_is_told_apart_from_the_colon_key() {
+    let mut keymap = keymap(&[
+        (":", "a"),
+        ("list::", "b"),
+        ("list:x", "a"),
+        ("list:x", "none"),
+    ]);
+    assert_eq!(press(&mut keymap, KeyCode::Char(':')), Some(Act::A));
+    keymap.set_pane(Some("list"));
+    assert_eq!(press(&mut keymap, KeyCode::Char(':')), Some(Act::B));
+    assert_eq!(press(&mut keymap, KeyCode::Char('x')), None);
+}
+
+// 部品の割り当てと全体の割り当ても、先頭が一致すれば押し分けられない
+#[test]
+fn pane_bindings_conflict_with_global_prefixes() {
+    let Err(errors) = Keymap::<Act>::new([("g g", "a"), ("list:g", "b"), ("detail:g", "a")]) else {
+        panic!("expected errors");
+    };
+    assert_eq!(errors.len(), 2);
+    assert!(Keymap::<Act>::new([("list:g", "a"), ("detail:g g", "b")]).is_ok());
+}
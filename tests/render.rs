@@ -0,0 +1,332 @@
+mod support;
+
+use crossterm::event::{KeyCode, MouseEventKind};
+use support::{alt, assert_snapshot, chars, click, ctrl, key, mouse, shift, Harness};
+
+#[tokio::test(start_paused = true)]
+async fn initial_screen() {
+    let harness = Harness::new("default", false);
+    let screen = harness.play(vec![]).await;
+    assert_snapshot("initial_screen", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn arrows_change_the_count() {
+    let harness = Harness::new("default", false);
+    let screen = harness
+        .play(vec![
+            key(KeyCode::Up),
+            key(KeyCode::Up),
+            key(KeyCode::Up),
+            key(KeyCode::Down),
+        ])
+        .await;
+    assert_snapshot("arrows_change_the_count", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn undo_and_redo() {
+    let harness = Harness::new("default", false);
+    let mut events = vec![key(KeyCode::Up), key(KeyCode::Up)];
+    events.extend(chars("uu"));
+    events.push(ctrl('r'));
+    let screen = harness.play(events).await;
+    assert_snapshot("undo_and_redo", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn mouse_clicks_buttons_and_scrolls() {
+    let harness = Harness::new("default", false);
+    // ボタンは右の枠の内側の5行目（派生値の行の下）: [ - ]が21..26列、[ + ]が27..32列
+    let screen = harness
+        .play(vec![
+            click(28, 5),
+            click(29, 5),
+            click(22, 5),
+            // ボタンの隙間とボタンの外は何もしない
+            click(26, 5),
+            click(40, 1),
+            mouse(MouseEventKind::ScrollUp, 35, 5),
+            mouse(MouseEventKind::ScrollUp, 35, 5),
+        ])
+        .await;
+    assert_snapshot("mouse_clicks_buttons_and_scrolls", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn help_overlay_lists_every_binding() {
+    let harness = Harness::new("vim", false).with_size(80, 27);
+    let screen = harness.play(chars("?")).await;
+    assert_snapshot("help_overlay_lists_every_binding", &screen);
+}
+
+// 標準的な80x24の端末では一覧が収まらないのでスクロールし、最後まで見られる
+#[tokio::test(start_paused = true)]
+async fn help_overlay_scrolls_when_it_does_not_fit() {
+    let harness = Harness::new("default", false).with_size(80, 24);
+    let screen = harness.play(chars("?")).await;
+    assert_snapshot("help_overlay_scrolls_when_it_does_not_fit", &screen);
+
+    // スクロール中の上下キーは一覧が受け取り、カウンターは変わらない
+    let mut events = chars("?");
+    events.extend([key(KeyCode::Down), key(KeyCode::End)]);
+    let screen = harness.play(events).await;
+    assert_snapshot("help_overlay_scrolls_to_the_end", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn help_overlay_toggles_off() {
+    let harness = Harness::new("default", false);
+    let screen = harness.play(chars("??")).await;
+    assert_snapshot("initial_screen", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn palette_filters_commands() {
+    let harness = Harness::new("default", false).with_size(80, 16);
+    let mut events = chars(":");
+    events.extend(chars("re"));
+    let screen = harness.play(events).await;
+    assert_snapshot("palette_filters_commands", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn palette_runs_the_selected_command() {
+    let harness = Harness::new("default", false);
+    let mut events = vec![ctrl('p')];
+    events.extend(chars("inc"));
+    events.push(key(KeyCode::Enter));
+    // 閉じた後のキーは通常どおりキーマップで解釈される
+    events.push(key(KeyCode::Up));
+    events.extend(chars(":dec"));
+    events.push(key(KeyCode::Esc));
+    let screen = harness.play(events).await;
+    assert_snapshot("palette_runs_the_selected_command", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn prompt_sets_and_adds_values() {
+    let harness = Harness::new("default", false);
+    let mut events = chars("=42");
+    events.push(key(KeyCode::Enter));
+    events.extend(chars("=+8"));
+    events.push(key(KeyCode::Enter));
+    events.extend(chars("=*2"));
+    events.push(key(KeyCode::Enter));
+    let screen = harness.play(events).await;
+    assert_snapshot("prompt_sets_and_adds_values", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn prompt_shows_validation_errors() {
+    let harness = Harness::new("default", false);
+    let mut events = chars("=+abc");
+    events.push(key(KeyCode::Enter));
+    let screen = harness.play(events).await;
+    assert_snapshot("prompt_shows_validation_errors", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn count_prefix_scales_the_action() {
+    let harness = Harness::new("vim", false);
+    let mut events = chars("10k");
+    events.extend(chars("3j"));
+    // 回数はその次のコマンドにだけ付く
+    events.extend(chars("k"));
+    events.extend(chars("2"));
+    let screen = harness.play(events).await;
+    assert_snapshot("count_prefix_scales_the_action", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn counters_are_added_renamed_and_reordered() {
+    let harness = Harness::new("default", false);
+    let mut events = chars("=new defects");
+    events.push(key(KeyCode::Enter));
+    events.extend([key(KeyCode::Up), key(KeyCode::Up)]);
+    events.extend(chars("n"));
+    events.extend(chars("=rename typos"));
+    events.push(key(KeyCode::Enter));
+    events.push(key(KeyCode::Up));
+    // typosを先頭に移動し、2行目のcounter 1をクリックで選んで削除する
+    events.extend([alt(KeyCode::Up), alt(KeyCode::Up)]);
+    events.push(click(3, 2));
+    events.push(key(KeyCode::Delete));
+    // クリックで一覧に移ったフォーカスを詳細に戻す
+    events.push(key(KeyCode::Tab));
+    events.push(shift(KeyCode::Up));
+    events.push(key(KeyCode::Down));
+    let screen = harness.play(events).await;
+    assert_snapshot("counters_are_added_renamed_and_reordered", &screen);
+}
+
+// 入力欄に順に入力して確定する
+fn prompts(lines: &[&str]) -> Vec<crossterm::event::Event> {
+    let mut events = Vec::new();
+    for line in lines {
+        events.extend(chars(&format!("={line}")));
+        events.push(key(KeyCode::Enter));
+    }
+    events
+}
+
+#[tokio::test(start_paused = true)]
+async fn bounds_saturate_by_default() {
+    let harness = Harness::new("default", false);
+    let screen = harness.play(prompts(&["max 10", "+15"])).await;
+    assert_snapshot("bounds_saturate_by_default", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn overflow_wraps_within_bounds() {
+    let harness = Harness::new("default", false);
+    let events = prompts(&["min 0", "max 59", "overflow wrap", "58", "+5"]);
+    let screen = harness.play(events).await;
+    assert_snapshot("overflow_wraps_within_bounds", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn overflow_error_keeps_the_value() {
+    let harness = Harness::new("default", false);
+    let events = prompts(&["overflow error", "2147483647", "+1"]);
+    let screen = harness.play(events).await;
+    assert_snapshot("overflow_error_keeps_the_value", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn overflow_promotes_to_big_integers() {
+    let harness = Harness::new("default", false).with_size(60, 10);
+    let events = prompts(&["overflow promote", "2147483647", "*2147483647", "*-4", "+1"]);
+    let screen = harness.play(events).await;
+    assert_snapshot("overflow_promotes_to_big_integers", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn input_after_quit_is_ignored() {
+    let harness = Harness::new("vim", false);
+    let mut events = chars("kk");
+    events.extend(chars("ZZ"));
+    events.extend(chars("kkk"));
+    let screen = harness.play(events).await;
+    assert_snapshot("input_after_quit_is_ignored", &screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn debug_pane_travels_back() {
+    let harness = Harness::new("default", true);
+    let mut events = vec![key(KeyCode::Up), key(KeyCode::Up), key(KeyCode::Up)];
+    events.extend(chars("[["));
+    let screen = harness.play(events).await;
+    assert_snapshot("debug_pane_travels_back", &screen);
+}
+
+// 終了の直前に入力したActionも適用してから終わる
+#[tokio::test(start_paused = true)]
+async fn quit_applies_pending_actions() {
+    let harness = Harness::new("default", false).interactive();
+    let screen = harness
+        .play(vec![
+            key(KeyCode::Up),
+            key(KeyCode::Up),
+            key(KeyCode::Char('q')),
+        ])
+        .await;
+    assert_snapshot("quit_applies_pending_actions", &screen);
+}
+
+// 上限を超えた古いActionは捨てられ、遡れるのは残っている最も古いActionまで
+#[tokio::test(start_paused = true)]
+async fn debug_log_keeps_only_the_latest_actions() {
+    let harness = Harness::new("default", true).with_log_depth(2);
+    let mut events = vec![key(KeyCode::Up); 5];
+    events.extend(chars("[[["));
+    let screen = harness.play(events).await;
+    assert_snapshot("debug_log_keeps_only_the_latest_actions", &screen);
+}
+
+#[cfg(feature = "scripting")]
+#[tokio::test(start_paused = true)]
+async fn macro_replays_recorded_keys() {
+    let harness = Harness::new("default", false).with_macros("macro_replays_recorded_keys");
+    let mut events = chars("m");
+    events.extend([key(KeyCode::Up), key(KeyCode::Up)]);
+    // 記録を止めると名前を聞かれる
+    events.extend(chars("mtwice"));
+    events.push(key(KeyCode::Enter));
+    events.extend(chars("@"));
+    let screen = harness.play(events).await;
+    assert_snapshot("macro_replays_recorded_keys", &screen);
+}
+
+// 保存したマクロは次の起動でも名前で選んで再生できる
+#[cfg(feature = "scripting")]
+#[tokio::test(start_paused = true)]
+async fn macro_is_played_by_name() {
+    let harness = Harness::new("default", false).with_macros("macro_is_played_by_name");
+    let mut events = chars("m");
+    events.extend([key(KeyCode::Up), key(KeyCode::Up), key(KeyCode::Up)]);
+    events.extend(chars("mthrice"));
+    events.push(key(KeyCode::Enter));
+    harness.play(events).await;
+
+    let mut events = chars("Mthrice");
+    events.push(key(KeyCode::Enter));
+    let screen = harness.play(events).await;
+    assert_snapshot("macro_is_played_by_name", &screen);
+}
+
+// 保存先の外を指す名前や、保存していない名前は入力欄にエラーを表示する
+#[cfg(feature = "scripting")]
+#[tokio::test(start_paused = true)]
+async fn macro_name_prompt_rejects_bad_names() {
+    let harness = Harness::new("default", false).with_macros("macro_name_prompt_rejects_bad_names");
+    let mut events = chars("m");
+    events.push(key(KeyCode::Up));
+    events.extend(chars("m../x"));
+    events.push(key(KeyCode::Enter));
+    let screen = harness.play(events).await;
+    assert_snapshot("macro_name_prompt_rejects_bad_names", &screen);
+
+    let mut events = chars("Mnope");
+    events.push(key(KeyCode::Enter));
+    let screen = harness.play(events).await;
+    assert_snapshot("macro_name_prompt_rejects_unknown_macros", &screen);
+}
+
+// 部品ごとの割り当ても設定で変えられ、フォーカスのある部品の割り当てがステータスバーに出る
+#[tokio::test(start_paused = true)]
+async fn user_keys_apply_to_the_focused_pane() {
+    let harness =
+        Harness::new("default", false).with_keys(&[("up", "none"), ("list:k", "counter.prev")]);
+    let mut events = chars("=new second");
+    events.push(key(KeyCode::Enter));
+    // 外した上キーは詳細にフォーカスがあっても何もしない
+    events.extend([key(KeyCode::Up), key(KeyCode::Up)]);
+    events.extend(chars("+"));
+    events.push(key(KeyCode::Tab));
+    events.extend(chars("k"));
+    let screen = harness.play(events).await;
+    assert_snapshot("user_keys_apply_to_the_focused_pane", &screen);
+}
+
+// Tab/Shift-Tabでフォーカスを移し、上下キーはフォーカスのある部品の割り当てになる
+#[tokio::test(start_paused = true)]
+async fn tab_moves_focus_between_panes() {
+    let harness = Harness::new("default", false);
+    let mut events = chars("=new second");
+    events.push(key(KeyCode::Enter));
+    // 一覧では上下キーで選択を移す
+    events.extend([key(KeyCode::Tab), key(KeyCode::Up)]);
+    // 詳細に戻れば上下キーで増減する
+    events.extend([shift(KeyCode::BackTab), key(KeyCode::Up), key(KeyCode::Up)]);
+    let screen = harness.play(events).await;
+    assert_snapshot("tab_moves_focus_between_panes", &screen);
+}
+
+// 部品をクリックするとフォーカスが移る（枠が太線になる）
+#[tokio::test(start_paused = true)]
+async fn clicking_a_pane_focuses_it() {
+    let harness = Harness::new("default", false);
+    let screen = harness.play(vec![click(3, 5), key(KeyCode::Up)]).await;
+    assert_snapshot("clicking_a_pane_focuses_it", &screen);
+}
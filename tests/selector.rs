@@ -0,0 +1,90 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use testtui::counter::{self, Counter, CounterState, Derived, Value};
+use testtui::selector;
+use testtui::Selector;
+use tokio::sync::watch;
+use tokio::time::Duration;
+
+// 入力が変わらなければ計算し直さない
+#[test]
+fn selector_recomputes_only_when_its_input_changes() {
+    let calls = Rc::new(Cell::new(0));
+    let counted = calls.clone();
+    let mut double = Selector::new(
+        |state: &(i32, &str)| state.0,
+        move |n: &i32| {
+            counted.set(counted.get() + 1);
+            n * 2
+        },
+    );
+
+    assert_eq!(*double.select(&(1, "a")), 2);
+    assert_eq!(*double.select(&(1, "b")), 2);
+    assert_eq!(calls.get(), 1);
+    assert_eq!(*double.select(&(3, "b")), 6);
+    assert_eq!(calls.get(), 2);
+    assert_eq!(double.version(), 2);
+}
+
+// 購読している派生値が変わったときだけ通知される
+#[tokio::test]
+async fn subscribe_notifies_only_when_the_slice_changes() {
+    let (tx, rx) = watch::channel((0, 0));
+    let mut first = selector::subscribe(rx, |state: &(i32, i32)| state.0);
+
+    tx.send((0, 1)).unwrap();
+    tx.send((2, 1)).unwrap();
+    first.changed().await.unwrap();
+    assert_eq!(*first.borrow_and_update(), 2);
+
+    tx.send((2, 5)).unwrap();
+    drop(tx);
+    // 元の状態がなくなるまでに通知はない
+    assert!(first.changed().await.is_err());
+}
+
+fn state(counts: &[i32], selected: usize) -> CounterState {
+    let counters = counts
+        .iter()
+        .enumerate()
+        .map(|(i, &n)| {
+            let mut counter = Counter::new(format!("c{i}"));
+            counter.count = Value::Int(n);
+            counter
+        })
+        .collect();
+    CounterState { counters, selected }
+}
+
+// 偶奇と合計は状態から、変化の速さは値が変わった時刻から求める
+#[tokio::test(start_paused = true)]
+async fn counter_stats_are_derived_from_the_state() {
+    let mut derived = Derived::new();
+    assert_eq!(derived.parity(&state(&[3, 4], 0)), "odd");
+    assert_eq!(derived.total(&state(&[3, 4], 0)).to_string(), "7");
+    assert_eq!(derived.rate(&state(&[3, 4], 0)), None);
+
+    tokio::time::advance(Duration::from_secs(2)).await;
+    assert_eq!(derived.rate(&state(&[7, 4], 0)), Some(2.0));
+
+    // 別のカウンターを選んだら測り直す
+    assert_eq!(derived.rate(&state(&[7, 4], 1)), None);
+    assert_eq!(derived.parity(&state(&[7, 4], 1)), "even");
+}
+
+// 値が変わらなくても時間とともに減衰し、期間を過ぎると測れなくなる
+#[tokio::test(start_paused = true)]
+async fn counter_rate_decays_while_the_value_stays() {
+    let mut derived = Derived::new();
+    assert_eq!(derived.rate(&state(&[3], 0)), None);
+    tokio::time::advance(Duration::from_secs(2)).await;
+    assert_eq!(derived.rate(&state(&[7], 0)), Some(2.0));
+
+    tokio::time::advance(Duration::from_secs(2)).await;
+    assert_eq!(derived.rate(&state(&[7], 0)), Some(1.0));
+
+    // 最初の変化が期間の外に出ると、期間内の変化が1つだけになる
+    tokio::time::advance(counter::RATE_WINDOW).await;
+    assert_eq!(derived.rate(&state(&[7], 0)), None);
+}
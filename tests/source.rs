@@ -0,0 +1,28 @@
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use testtui::source::RecordedEvent;
+use testtui::{InputSource, ReplayInput};
+use tokio::time::{Duration, Instant};
+
+fn key(delay_ms: u64) -> RecordedEvent {
+    RecordedEvent {
+        delay_ms,
+        event: Event::Key(KeyEvent::from(KeyCode::Char('a'))),
+    }
+}
+
+// 記録時の間隔をspeed倍の速さで待ってから流す
+#[tokio::test(start_paused = true)]
+async fn replay_waits_for_the_recorded_delay() {
+    let mut input = ReplayInput::new([key(1000)]).with_speed(2.0);
+    let start = Instant::now();
+    assert!(input.next_event().await.is_some());
+    assert_eq!(start.elapsed(), Duration::from_millis(500));
+}
+
+// 倍率が極端に小さくても待ち時間が溢れてpanicしない
+#[tokio::test(start_paused = true)]
+async fn tiny_speed_does_not_overflow_the_delay() {
+    let mut input = ReplayInput::new([key(u64::MAX)]).with_speed(1e-300);
+    let waited = tokio::time::timeout(Duration::from_secs(60), input.next_event()).await;
+    assert!(waited.is_err());
+}
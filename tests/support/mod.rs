@@ -0,0 +1,215 @@
+// 描画テストの共通部分: TestBackendの上でViewを動かし、ゴールデンファイルと比較する
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use futures::future::LocalBoxFuture;
+use ratatui::{backend::TestBackend, Terminal};
+use std::io;
+use std::path::PathBuf;
+use testtui::counter::{self, CounterAction, CounterState, CounterStore};
+use testtui::{
+    DebugPane, Dispatcher, History, InputSource, Keymap, KeymapConfig, Recorder, ScriptedInput,
+    Timeline, View,
+};
+
+pub const WIDTH: u16 = 50;
+pub const HEIGHT: u16 = 9;
+
+pub type CounterView = View<TestBackend, Timeline<CounterState>, CounterAction>;
+
+// Harness: 用意したイベント列でカウンターのViewを動かす
+pub struct Harness {
+    pub preset: &'static str,
+    pub debug: bool,
+    // デバッグパネルに残すActionの数
+    pub log_depth: usize,
+    // 画面の大きさ（幅、高さ）
+    pub size: (u16, u16),
+    // マクロの保存先（Noneならマクロを使わない）
+    #[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+    pub macros: Option<PathBuf>,
+    // 人が操作しているものとして動かす（入力ごとにActionの適用を待たない）
+    pub interactive: bool,
+    // 設定ファイルでプリセットに重ねるキー割り当て
+    pub keys: Vec<(&'static str, &'static str)>,
+}
+
+impl Harness {
+    pub fn new(preset: &'static str, debug: bool) -> Self {
+        Self {
+            preset,
+            debug,
+            log_depth: 100,
+            size: (WIDTH, HEIGHT),
+            macros: None,
+            interactive: false,
+            keys: Vec::new(),
+        }
+    }
+
+    pub fn with_log_depth(mut self, depth: usize) -> Self {
+        self.log_depth = depth;
+        self
+    }
+
+    pub fn interactive(mut self) -> Self {
+        self.interactive = true;
+        self
+    }
+
+    pub fn with_keys(mut self, keys: &[(&'static str, &'static str)]) -> Self {
+        self.keys = keys.to_vec();
+        self
+    }
+
+    pub fn with_size(mut self, width: u16, height: u16) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    // テストごとに空の一時ディレクトリへマクロを保存する
+    #[cfg(feature = "scripting")]
+    pub fn with_macros(mut self, name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("testtui-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        self.macros = Some(dir);
+        self
+    }
+
+    fn view(&self, events: Vec<Event>) -> CounterView {
+        let mut builder = Dispatcher::builder();
+        let (id, state) = builder.register(History::new(CounterStore::new(), 100));
+        let log = self.debug.then(|| {
+            let (recorder, log) = Recorder::new(id, self.log_depth);
+            builder.middleware(recorder);
+            log
+        });
+        let dispatcher = builder.spawn();
+
+        let config = KeymapConfig {
+            preset: Some(self.preset.to_owned()),
+            keys: self
+                .keys
+                .iter()
+                .map(|(k, c)| (k.to_string(), c.to_string()))
+                .collect(),
+        };
+        let keymap = Keymap::from_config(counter::PRESETS, &config).unwrap();
+        let (width, height) = self.size;
+        let terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let mut view = View::new(
+            terminal,
+            Box::new(Scripted {
+                events: ScriptedInput::new(events),
+                interactive: self.interactive,
+            }),
+            dispatcher,
+            state,
+            counter::view(),
+            keymap,
+        )
+        .with_prompt(Box::new(counter::parse));
+        if let Some(log) = log {
+            view = view.with_debug(DebugPane::new(log));
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(dir) = &self.macros {
+            view = view.with_macros(testtui::Macros::new(dir.clone(), 1.0));
+        }
+        view
+    }
+
+    // 入力を最後まで流し、すべてのActionが適用された後の画面を返す
+    pub async fn play(&self, events: Vec<Event>) -> String {
+        let mut view = self.view(events);
+        view.run().await.unwrap();
+        view.draw().unwrap();
+        screen(view.terminal().backend())
+    }
+}
+
+// Scripted: 用意したイベント列を、人が操作しているものとしても流せる
+struct Scripted {
+    events: ScriptedInput,
+    interactive: bool,
+}
+
+impl InputSource for Scripted {
+    fn next_event(&mut self) -> LocalBoxFuture<'_, Option<io::Result<Event>>> {
+        self.events.next_event()
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+}
+
+pub fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+pub fn ctrl(c: char) -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+}
+
+pub fn alt(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::ALT))
+}
+
+pub fn shift(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::SHIFT))
+}
+
+pub fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+    Event::Mouse(MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+pub fn click(column: u16, row: u16) -> Event {
+    mouse(MouseEventKind::Down(MouseButton::Left), column, row)
+}
+
+pub fn chars(text: &str) -> Vec<Event> {
+    text.chars().map(|c| key(KeyCode::Char(c))).collect()
+}
+
+// 画面の文字だけを行ごとに取り出す
+pub fn screen(backend: &TestBackend) -> String {
+    let buffer = backend.buffer();
+    let area = buffer.area;
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            text.push_str(buffer[(x, y)].symbol());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+// tests/snapshots/<name>.txtと比較する
+// UPDATE_SNAPSHOTS=1のときは比較せずに書き直す
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "cannot read {}: {e}\nrun with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "snapshot {name} differs\n--- expected\n{expected}--- actual\n{actual}\
+         run with UPDATE_SNAPSHOTS=1 to accept the new rendering"
+    );
+}